@@ -1,6 +1,6 @@
 use std::{net::SocketAddr, path::Path};
 
-use jsonrpsee::server::Server;
+use jsonrpsee::server::{Server, middleware::rpc::RpcServiceBuilder};
 use openspec_jsonrpsee::{SpecModule, rpc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -17,7 +17,8 @@ pub struct User {
     age: i64,
 }
 
-#[rpc]
+// Calls beyond the connections of the database pool would only wait for one
+#[rpc(max_concurrency = 5)]
 pub async fn register_user(#[context] ctx: &Ctx, name: String, age: i64) -> Result<i64, String> {
     sqlx::query("INSERT INTO user (name, age) VALUES (?, ?)")
         .bind(name)
@@ -51,6 +52,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .write_spec(Path::new("./spec.json"))?;
 
     let server = Server::builder()
+        .set_rpc_middleware(RpcServiceBuilder::new().layer(module.rpc_middleware()))
         .build(
             SERVER_ADDR
                 .parse::<SocketAddr>()
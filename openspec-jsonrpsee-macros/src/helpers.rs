@@ -5,6 +5,95 @@ pub fn extract_return_type<'a>(input: &'a syn::ItemFn) -> syn::Type {
     }
 }
 
+/// Returns `T` if the type is `RpcResult<T>` or `Result<T, ErrorObjectOwned>` (or `ErrorObject<'static>`),
+/// which the handler returns as is rather than wrapping it in `Ok`
+pub fn rpc_result_ok_type(ty: &syn::Type) -> Option<syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let types: Vec<&syn::Type> = args
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect();
+    match (segment.ident.to_string().as_str(), types.as_slice()) {
+        ("RpcResult", [ok]) => Some((*ok).clone()),
+        ("Result", [ok, syn::Type::Path(error)]) => {
+            let error = error.path.segments.last()?;
+            (error.ident == "ErrorObjectOwned" || error.ident == "ErrorObject")
+                .then(|| (*ok).clone())
+        }
+        _ => None,
+    }
+}
+
+/// Returns `T` if the type is `RpcStream<T>`, whose items are sent to the subscriber of the method
+pub fn rpc_stream_item_type(ty: &syn::Type) -> Option<syn::Type> {
+    match type_args(ty, "RpcStream")?.as_slice() {
+        [item] => Some((*item).clone()),
+        _ => None,
+    }
+}
+
+/// Returns the type described by the result of the method: the return type, unwrapped from an
+/// `RpcResult` and then an `RpcStream`
+pub fn response_type(ty: &syn::Type) -> syn::Type {
+    let ty = rpc_result_ok_type(ty).unwrap_or_else(|| ty.clone());
+    rpc_stream_item_type(&ty).unwrap_or(ty)
+}
+
+/// Returns the type arguments of the last segment of the type if it's named `name`, e.g. `[T]` for
+/// `RpcStream<T>` with `"RpcStream"`
+fn type_args<'a>(ty: &'a syn::Type, name: &str) -> Option<Vec<&'a syn::Type>> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::None => Some(Vec::new()),
+        syn::PathArguments::AngleBracketed(args) => Some(
+            args.args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        syn::PathArguments::Parenthesized(_) => None,
+    }
+}
+
+/// Returns the type of the items and of the cursor if the type is `Page<T>` (of `String` cursors)
+/// or `Page<T, Cursor>`
+pub fn page_types(ty: &syn::Type) -> Option<(syn::Type, syn::Type)> {
+    match type_args(ty, "Page")?.as_slice() {
+        [item] => Some(((*item).clone(), syn::parse_quote!(String))),
+        [item, cursor] => Some(((*item).clone(), (*cursor).clone())),
+        _ => None,
+    }
+}
+
+/// Returns the type of the cursor if the type is `PageRequest` (of `String` cursors) or
+/// `PageRequest<Cursor>`
+pub fn page_request_cursor_type(ty: &syn::Type) -> Option<syn::Type> {
+    match type_args(ty, "PageRequest")?.as_slice() {
+        [] => Some(syn::parse_quote!(String)),
+        [cursor] => Some((*cursor).clone()),
+        _ => None,
+    }
+}
+
 // Turns a &T into a T
 pub fn remove_type_ref(ty: &syn::Type) -> syn::Type {
     match ty {
@@ -1,5 +1,6 @@
 pub(crate) mod helpers;
 pub(crate) mod rpc_method;
+pub(crate) mod rpc_service;
 
 use proc_macro::TokenStream;
 use proc_macro_error::proc_macro_error;
@@ -8,6 +9,12 @@ use proc_macro_error::proc_macro_error;
 #[proc_macro_attribute]
 pub fn rpc(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = rpc_method::RpcMethodArgs::parse(args);
-    let input = syn::parse_macro_input!(item as syn::ItemFn);
-    rpc_method::generate_rpc_method(input, args)
+    match syn::parse_macro_input!(item as syn::Item) {
+        syn::Item::Fn(input) => rpc_method::generate_rpc_method(input, args),
+        syn::Item::Impl(input) => rpc_service::generate_rpc_service(input, args),
+        item => proc_macro_error::abort!(
+            item,
+            "`#[rpc]` can only be used on functions and impl blocks"
+        ),
+    }
 }
@@ -0,0 +1,111 @@
+//! Checks of the traits needed on the types of the method, spanned on the types so a missing trait
+//! is reported on the signature with a message of its own. The errors of its uses in the generated
+//! code are still reported alongside it, and the bounds of the `RpcMethod` impl itself (e.g. a
+//! result missing `Clone`) are reported before it.
+use super::model::RpcMethod;
+use crate::helpers::owned_type_version;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::{Type, spanned::Spanned};
+
+/// A trait needed on a type of the method, see `openspec_jsonrpsee::macro_support`
+pub enum Check {
+    ServerParam,
+    ServerResult,
+    ServerOwnedContext,
+    ClientParam,
+    ClientResult,
+}
+
+impl Check {
+    fn assert_fn(&self) -> TokenStream2 {
+        match self {
+            Check::ServerParam => quote! { assert_server_param },
+            Check::ServerResult => quote! { assert_server_result },
+            Check::ServerOwnedContext => quote! { assert_server_owned_context },
+            Check::ClientParam => quote! { assert_client_param },
+            Check::ClientResult => quote! { assert_client_result },
+        }
+    }
+}
+
+/// Returns the checks of the traits the server needs on the types of the method
+pub fn server(model: &RpcMethod) -> Vec<(Type, Check)> {
+    let params = model
+        .fn_args_contextless
+        .iter()
+        // Borrowed params are deserialized into their owned version
+        .map(|arg| (owned_type_version(&arg.ty), Check::ServerParam));
+    let result = std::iter::once((model.response_ty.clone(), Check::ServerResult));
+    let context = (model.context_ident.is_some() && !model.context_ty_referenced)
+        .then(|| (model.context_ty.clone(), Check::ServerOwnedContext));
+
+    params.chain(result).chain(context).collect()
+}
+
+/// Returns the checks of the traits the client needs on the types of the method
+pub fn client(model: &RpcMethod, with_result: bool) -> Vec<(Type, Check)> {
+    let params = model
+        .fn_args_contextless
+        .iter()
+        .map(|arg| ((*arg.ty).clone(), Check::ClientParam));
+    let result = with_result.then(|| (model.response_ty.clone(), Check::ClientResult));
+
+    params.chain(result).collect()
+}
+
+/// Generates the assertions of the checks, as statements
+pub fn assertions(checks: &[(Type, Check)]) -> TokenStream2 {
+    let assertions = checks.iter().map(|(ty, check)| {
+        let ty_static = static_lifetimes(ty);
+        let assert_fn = check.assert_fn();
+        quote_spanned! { ty.span()=>
+            ::openspec_jsonrpsee::macro_support::#assert_fn::<#ty_static>();
+        }
+    });
+    quote! { #(#assertions)* }
+}
+
+/// Makes the elided lifetimes of references `'static`, which the turbofish of the assertions can't elide
+fn static_lifetimes(ty: &Type) -> TokenStream2 {
+    fn replace(tokens: TokenStream2) -> TokenStream2 {
+        let mut output = Vec::new();
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Punct(punct) if punct.as_char() == '&' => {
+                    let span = punct.span();
+                    output.push(TokenTree::Punct(punct));
+                    let has_lifetime = matches!(
+                        tokens.peek(),
+                        Some(TokenTree::Punct(next)) if next.as_char() == '\''
+                    );
+                    if !has_lifetime {
+                        output.extend(quote_spanned! { span=> 'static });
+                    }
+                }
+                TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                    // `'_` is elided as well
+                    match tokens.next() {
+                        Some(TokenTree::Ident(ident)) if ident == "_" => {
+                            output.extend(quote_spanned! { ident.span()=> 'static });
+                        }
+                        next => {
+                            output.push(TokenTree::Punct(punct));
+                            output.extend(next);
+                        }
+                    }
+                }
+                TokenTree::Group(group) => {
+                    let mut replaced =
+                        proc_macro2::Group::new(group.delimiter(), replace(group.stream()));
+                    replaced.set_span(group.span());
+                    output.push(TokenTree::Group(replaced));
+                }
+                token => output.push(token),
+            }
+        }
+        output.into_iter().collect()
+    }
+    replace(quote! { #ty })
+}
@@ -2,37 +2,81 @@ use crate::rpc_method::{RpcMethodArgs, model::RpcMethod};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
-pub fn generate(
-    RpcMethod {
-        input_vis,
-        input_ident,
-        fn_args_contextless,
-        fn_args_contextless_as_ident,
-        response_ty,
-        ..
-    }: &RpcMethod,
-    RpcMethodArgs {
-        client,
-        client_field,
-    }: &RpcMethodArgs,
-) -> TokenStream2 {
+pub fn generate(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
     #[cfg(not(feature = "client"))]
     {
+        let _ = (model, args);
         quote! {}
     }
     #[cfg(feature = "client")]
     {
+        let RpcMethod {
+            input_vis,
+            input_ident,
+            fn_args_contextless,
+            response_ty,
+            deprecated,
+            ..
+        } = model;
+        let wire_params = model.wire_params();
+        let RpcMethodArgs {
+            client,
+            client_field,
+            notification,
+            ..
+        } = args;
         if client.is_none() {
             return quote! {};
         }
         let client_field = client_field.clone().unwrap_or(syn::parse_quote!(client));
+        let scopes_doc = super::request::scopes_doc(args);
+
+        if *notification {
+            return quote! {
+                impl #client {
+                    #scopes_doc
+                    #deprecated
+                    #input_vis async fn #input_ident(&self, #fn_args_contextless) -> ::std::result::Result<(), ::jsonrpsee::core::ClientError> {
+                        use ::jsonrpsee::core::client::ClientT as _;
+
+                        let params = ::jsonrpsee::rpc_params!(#wire_params);
+                        self.#client_field
+                            .notification(stringify!(#input_ident), params)
+                            .await
+                    }
+                }
+            };
+        }
+
+        if model.returns_stream {
+            return quote! {
+                impl #client {
+                    #scopes_doc
+                    #deprecated
+                    #input_vis async fn #input_ident(&self, #fn_args_contextless) -> ::std::result::Result<::jsonrpsee::core::client::Subscription<#response_ty>, ::jsonrpsee::core::ClientError> {
+                        use ::jsonrpsee::core::client::SubscriptionClientT as _;
+
+                        let params = ::jsonrpsee::rpc_params!(#wire_params);
+                        self.#client_field
+                            .subscribe::<#response_ty, _>(
+                                stringify!(#input_ident),
+                                params,
+                                &::openspec_jsonrpsee::unsubscribe_method_name(stringify!(#input_ident)),
+                            )
+                            .await
+                    }
+                }
+            };
+        }
 
         quote! {
             impl #client {
+                #scopes_doc
+                #deprecated
                 #input_vis async fn #input_ident(&self, #fn_args_contextless) -> ::std::result::Result<#response_ty, ::jsonrpsee::core::ClientError> {
                     use ::jsonrpsee::core::client::ClientT as _;
 
-                    let params = ::jsonrpsee::rpc_params!(#fn_args_contextless_as_ident);
+                    let params = ::jsonrpsee::rpc_params!(#wire_params);
                     let response = self.#client_field
                         .request::<#response_ty, _>(stringify!(#input_ident), params)
                         .await;
@@ -1,123 +1,287 @@
-use super::model::RpcMethod;
+use super::{
+    checks,
+    model::{RpcMethod, RpcMethodArgs},
+};
+use crate::helpers::{owned_type_version, page_request_cursor_type};
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{Ident, PatType, punctuated::Punctuated, token::Comma};
+use quote::{ToTokens, quote};
+use syn::Ident;
 
-pub fn generate(model: &RpcMethod) -> TokenStream2 {
-    let fn_input = &model.input_ident;
-    let arguments_parse_impl = gen_arguments_parse_impl(&model.fn_args_contextless);
+pub fn generate(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
+    let RpcMethod {
+        context_ty_owned,
+        response_ty,
+        ..
+    } = model;
+    let fn_input = &model.fn_path;
+    let arguments_parse_impl = gen_arguments_parse_impl(model);
     let context_ident = model
         .context_ident
         .clone()
         .unwrap_or_else(|| Ident::new("_context", model.input_span.clone()));
 
-    if model.input_async.is_some() {
+    let checks = checks::assertions(&checks::server(model));
+
+    let handler = if model.returns_stream {
+        generate_stream_handler(model, &context_ident, &arguments_parse_impl, &fn_input)
+    } else if model.input_async.is_some() {
         generate_async_handler(model, &context_ident, &arguments_parse_impl, &fn_input)
+    } else if args.blocking {
+        generate_blocking_handler(model, &context_ident, &arguments_parse_impl, &fn_input)
     } else {
         generate_sync_handler(model, &context_ident, &arguments_parse_impl, &fn_input)
+    };
+
+    quote! {
+        #[allow(clippy::ptr_arg)] // Suppressed due to complexity in generating for all context types
+        fn handler(&self) -> ::openspec_jsonrpsee::ServerHandler<#context_ty_owned, ::jsonrpsee::core::RpcResult<#response_ty>> {
+            #checks
+
+            #handler
+        }
     }
 }
 
 fn generate_async_handler(
-    RpcMethod {
+    model: &RpcMethod,
+    context_ident: &Ident,
+    arguments_parse_impl: &TokenStream2,
+    fn_input: &TokenStream2,
+) -> TokenStream2 {
+    let RpcMethod {
         context_ty_owned,
-        context_ident: ctx,
-        context_ty_referenced,
+        generics,
+        cancellation_ident,
         response_ty,
-        fn_args_as_ident,
         ..
-    }: &RpcMethod,
+    } = model;
+    let fn_args_stream = gen_fn_args(model, true);
+    let respond = gen_respond(model);
+    let cancellation = cancellation_ident.as_ref().map(|ident| {
+        quote::quote! {
+            let #ident = _ext
+                .get::<::openspec_jsonrpsee::CancellationToken>()
+                .cloned()
+                .unwrap_or_default();
+        }
+    });
+
+    let (generic_params, where_clause) = (&generics.params, &generics.where_clause);
+    let turbofish = turbofish(generics);
+
+    quote::quote! {
+        fn callback_wrapper<#generic_params>(
+            params: ::jsonrpsee::types::Params<'static>,
+            #context_ident: ::std::sync::Arc<#context_ty_owned>,
+            _ext: ::jsonrpsee::Extensions,
+        ) -> ::std::pin::Pin<
+            Box<dyn ::std::future::Future<Output = ::jsonrpsee::core::RpcResult<#response_ty>> + Send>,
+        > #where_clause {
+            Box::pin(async move {
+                #arguments_parse_impl
+                #cancellation
+                let response = #fn_input(#fn_args_stream).await;
+                #respond
+            })
+        }
+
+        ::openspec_jsonrpsee::ServerHandler::Async(callback_wrapper #turbofish)
+    }
+}
+
+fn generate_sync_handler(
+    model: &RpcMethod,
     context_ident: &Ident,
     arguments_parse_impl: &TokenStream2,
-    fn_input: &Ident,
+    fn_input: &TokenStream2,
 ) -> TokenStream2 {
-    let fn_args_stream = fn_args_as_ident
-        .iter()
-        .map(|arg| {
-            if ctx.as_ref().is_some_and(|ctx| ctx == arg) {
-                if *context_ty_referenced {
-                    quote::quote! { &#arg }
-                } else {
-                    quote::quote! { (*#arg).clone() }
-                }
-            } else {
-                quote::quote! { #arg }
-            }
-        })
-        .collect::<Vec<_>>();
-    let fn_args_stream = quote! { #(#fn_args_stream),* };
+    let RpcMethod {
+        context_ty_owned,
+        generics,
+        response_ty,
+        ..
+    } = model;
+    let fn_args_stream = gen_fn_args(model, false);
+    let respond = gen_respond(model);
+    let (generic_params, where_clause) = (&generics.params, &generics.where_clause);
+    let turbofish = turbofish(generics);
 
     quote::quote! {
-        #[allow(clippy::ptr_arg)] // Suppressed due to complexity in generating for all context types
-        fn handler(&self) -> ::openspec_jsonrpsee::ServerHandler<#context_ty_owned, ::jsonrpsee::core::RpcResult<#response_ty>> {
-            fn callback_wrapper(
-                params: ::jsonrpsee::types::Params<'static>,
-                #context_ident: ::std::sync::Arc<#context_ty_owned>,
-                _ext: ::jsonrpsee::Extensions,
-            ) -> ::std::pin::Pin<
-                Box<dyn ::std::future::Future<Output = ::jsonrpsee::core::RpcResult<#response_ty>> + Send>,
-            > {
-                Box::pin(async move {
-                    #arguments_parse_impl
-                    let response = #fn_input(#fn_args_stream).await;
-                    Ok(response)
-                })
-            }
-
-            ::openspec_jsonrpsee::ServerHandler::Async(callback_wrapper)
+        fn callback_wrapper<'a, 'b, 'c, #generic_params>(
+            params: ::jsonrpsee::types::Params<'a>,
+            #context_ident: &'b #context_ty_owned,
+            _ext: &'c ::jsonrpsee::Extensions,
+        ) -> ::jsonrpsee::core::RpcResult<#response_ty> #where_clause {
+            #arguments_parse_impl
+            let response = #fn_input(#fn_args_stream);
+            #respond
         }
+
+        ::openspec_jsonrpsee::ServerHandler::Sync(callback_wrapper #turbofish)
     }
 }
 
-fn generate_sync_handler(
-    RpcMethod {
+fn generate_blocking_handler(
+    model: &RpcMethod,
+    context_ident: &Ident,
+    arguments_parse_impl: &TokenStream2,
+    fn_input: &TokenStream2,
+) -> TokenStream2 {
+    let RpcMethod {
         context_ty_owned,
+        generics,
         response_ty,
-        fn_args_as_ident,
         ..
-    }: &RpcMethod,
+    } = model;
+    let fn_args_stream = gen_fn_args(model, true);
+    let respond = gen_respond(model);
+
+    let (generic_params, where_clause) = (&generics.params, &generics.where_clause);
+    let turbofish = turbofish(generics);
+
+    quote::quote! {
+        fn callback_wrapper<#generic_params>(
+            params: ::jsonrpsee::types::Params,
+            #context_ident: ::std::sync::Arc<#context_ty_owned>,
+            _ext: ::jsonrpsee::Extensions,
+        ) -> ::jsonrpsee::core::RpcResult<#response_ty> #where_clause {
+            #arguments_parse_impl
+            let response = #fn_input(#fn_args_stream);
+            #respond
+        }
+
+        ::openspec_jsonrpsee::ServerHandler::Blocking(callback_wrapper #turbofish)
+    }
+}
+
+fn generate_stream_handler(
+    model: &RpcMethod,
     context_ident: &Ident,
     arguments_parse_impl: &TokenStream2,
-    fn_input: &Ident,
+    fn_input: &TokenStream2,
 ) -> TokenStream2 {
+    let RpcMethod {
+        context_ty_owned,
+        generics,
+        response_ty,
+        ..
+    } = model;
+    let fn_args_stream = gen_fn_args(model, true);
+    let await_response = model.input_async.map(|_| quote! { .await });
+    // An `RpcResult` of a stream rejects the subscription with its error
+    let respond = if model.returns_rpc_result {
+        quote! { response.map(::openspec_jsonrpsee::RpcStream::into_stream) }
+    } else {
+        quote! { Ok(::openspec_jsonrpsee::RpcStream::into_stream(response)) }
+    };
+
+    let (generic_params, where_clause) = (&generics.params, &generics.where_clause);
+    let turbofish = turbofish(generics);
+
     quote::quote! {
-        #[allow(clippy::ptr_arg)] // Suppressed due to complexity in generating for all context types
-        fn handler(&self) -> ::openspec_jsonrpsee::ServerHandler<#context_ty_owned, ::jsonrpsee::core::RpcResult<#response_ty>> {
-            fn callback_wrapper<'a, 'b, 'c>(
-                params: ::jsonrpsee::types::Params<'a>,
-                #context_ident: &'b #context_ty_owned,
-                _ext: &'c ::jsonrpsee::Extensions,
-            ) -> ::jsonrpsee::core::RpcResult<#response_ty> {
+        fn callback_wrapper<#generic_params>(
+            params: ::jsonrpsee::types::Params<'static>,
+            #context_ident: ::std::sync::Arc<#context_ty_owned>,
+            _ext: ::jsonrpsee::Extensions,
+        ) -> ::std::pin::Pin<
+            Box<
+                dyn ::std::future::Future<
+                    Output = ::jsonrpsee::core::RpcResult<
+                        ::openspec_jsonrpsee::ResponseStream<::jsonrpsee::core::RpcResult<#response_ty>>,
+                    >,
+                > + Send,
+            >,
+        > #where_clause {
+            Box::pin(async move {
                 #arguments_parse_impl
-                let response = #fn_input(#fn_args_as_ident);
-                Ok(response)
-            }
-
-            ::openspec_jsonrpsee::ServerHandler::Sync(callback_wrapper)
+                let response = #fn_input(#fn_args_stream) #await_response;
+                #respond
+            })
         }
+
+        ::openspec_jsonrpsee::ServerHandler::Stream(callback_wrapper #turbofish)
     }
 }
 
-fn gen_arguments_parse_impl(fn_args_contextless: &Punctuated<PatType, Comma>) -> TokenStream2 {
-    use syn::{Pat, Type};
+/// The generic args of the callback wrapper, which redeclares the generics of a generic method
+fn turbofish(generics: &syn::Generics) -> TokenStream2 {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    ty_generics.as_turbofish().into_token_stream()
+}
+
+/// Responds with the result of the input, passed through if it's already an `RpcResult`
+fn gen_respond(model: &RpcMethod) -> TokenStream2 {
+    if model.returns_rpc_result {
+        quote! { response }
+    } else {
+        quote! { Ok(response) }
+    }
+}
 
-    let pat: Punctuated<Pat, Comma> = fn_args_contextless
+/// Passes the args to the input fn: the context from an `Arc` (by reference or cloned) if
+/// `context_from_arc`, and the borrowed params by reference to their owned value
+fn gen_fn_args(model: &RpcMethod, context_from_arc: bool) -> TokenStream2 {
+    let fn_args_stream = model
+        .fn_args
         .iter()
-        .map(|pat_type| (*pat_type.pat).clone())
-        .collect();
+        .zip(&model.fn_args_as_ident)
+        .map(|(pat_type, arg)| {
+            if model.context_ident.as_ref() == Some(arg) {
+                match (context_from_arc, model.context_ty_referenced) {
+                    (true, true) => quote::quote! { &#arg },
+                    (true, false) => quote::quote! { (*#arg).clone() },
+                    (false, _) => quote::quote! { #arg },
+                }
+            } else if matches!(*pat_type.ty, syn::Type::Reference(_)) {
+                quote::quote! { &#arg }
+            } else {
+                quote::quote! { #arg }
+            }
+        })
+        .collect::<Vec<_>>();
+    quote! { #(#fn_args_stream),* }
+}
 
-    let ty: Punctuated<Type, Comma> = fn_args_contextless
+/// Parses the params into the args, the `PageRequest` of a paginated method from the `cursor` and
+/// `limit` params
+fn gen_arguments_parse_impl(model: &RpcMethod) -> TokenStream2 {
+    let mut pat: Vec<TokenStream2> = Vec::new();
+    let mut ty: Vec<TokenStream2> = Vec::new();
+    let mut page_request = None;
+    for (pat_type, ident) in model
+        .fn_args_contextless
         .iter()
-        .map(|pat_type| (*pat_type.ty).clone())
-        .collect();
+        .zip(&model.fn_args_contextless_as_ident)
+    {
+        match page_request_cursor_type(&pat_type.ty) {
+            Some(cursor_ty) if model.page_ident.as_ref() == Some(ident) => {
+                pat.push(quote! { __cursor, __limit });
+                ty.push(quote! { ::std::option::Option<#cursor_ty>, ::std::option::Option<u32> });
+                page_request = Some(quote! {
+                    let #ident = ::openspec_jsonrpsee::PageRequest {
+                        cursor: __cursor,
+                        limit: __limit,
+                    };
+                });
+            }
+            _ => {
+                let param_pat = &pat_type.pat;
+                let param_ty = owned_type_version(&pat_type.ty);
+                pat.push(quote! { #param_pat });
+                ty.push(quote! { #param_ty });
+            }
+        }
+    }
 
-    // Determine the appropriate parsing method based on the number of arguments
-    let fn_parse = match fn_args_contextless.len() {
+    // Determine the appropriate parsing method based on the number of params
+    let params_len = model.fn_args_contextless.len() + usize::from(page_request.is_some());
+    let fn_parse = match params_len {
         1 => quote::quote! { one },
         _ => quote::quote! { parse },
     };
 
     quote::quote! {
-        let (#pat): (#ty) = params.#fn_parse()?;
+        let (#(#pat),*): (#(#ty),*) = params.#fn_parse()?;
+        #page_request
     }
 }
@@ -1,3 +1,4 @@
+mod checks;
 mod client;
 mod handler;
 mod model;
@@ -11,64 +12,169 @@ pub(crate) use model::RpcMethodArgs;
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::Ident;
+pub(crate) use sanitize_input::fn_arg_drop_attrs;
+use syn::Type;
 
 pub fn generate_rpc_method(input: syn::ItemFn, args: RpcMethodArgs) -> TokenStream {
-    let model = RpcMethod::parse(input.clone());
-    let RpcMethod {
-        input_ident,
-        output_ident,
-        ..
-    } = model.clone();
-
+    args.validate(&input);
     let sanitize_input = sanitize_input::generate(&input);
+    let items = generate_items(&input, None, &args);
+
+    quote::quote! {
+        #sanitize_input
 
+        #items
+    }
+    .into()
+}
+
+/// Generates the struct of the method and its implementations.
+///
+/// For methods of an `impl` block, `self_ty` is the type of the block: the input is the method
+/// with `&self` turned into its `#[context]`, the method is called through the type, and the struct
+/// is prefixed with the type, e.g. `UserServiceList`, so services can have methods of a same name.
+pub(crate) fn generate_items(
+    input: &syn::ItemFn,
+    self_ty: Option<&Type>,
+    args: &RpcMethodArgs,
+) -> TokenStream2 {
+    let mut model = RpcMethod::parse(input.clone());
+    if let Some(self_ty) = self_ty {
+        let input_ident = &model.input_ident;
+        model.fn_path = quote! { <#self_ty>::#input_ident };
+        model.output_ident = service_output_ident(self_ty, &model.output_ident);
+    }
     // Client features
-    let impl_requests = gen_impl_requests(&model);
-    let impl_client = client::generate(&model, &args);
+    let impl_requests = gen_impl_requests(&model, args);
+    let impl_client = client::generate(&model, args);
 
     // Server features
-    let impl_rpc_method = gen_impl_rpc_method(&input, &input_ident, &model);
+    let impl_rpc_method = gen_impl_rpc_method(input, &model, args);
 
-    quote::quote! {
-        #sanitize_input
+    let struct_def = gen_struct(&model);
 
-        #[allow(non_camel_case_types)]
-        pub struct #output_ident;
+    quote::quote! {
+        #struct_def
 
         #impl_requests
         #impl_client
 
         #impl_rpc_method
     }
-    .into()
 }
 
-fn gen_fn_name(fn_name: &Ident) -> TokenStream2 {
+/// The struct of a method of a service, prefixed with the type of the service
+pub(crate) fn service_output_ident(self_ty: &Type, output_ident: &syn::Ident) -> syn::Ident {
+    let service = match self_ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| &segment.ident),
+        _ => None,
+    };
+    match service {
+        Some(service) => syn::Ident::new(&format!("{service}{output_ident}"), output_ident.span()),
+        None => output_ident.clone(),
+    }
+}
+
+/// Generates the struct of the method: a unit struct, or for a generic method a struct holding the
+/// name it's instantiated under, e.g. `List::<User>::named("users.list")`
+fn gen_struct(model: &RpcMethod) -> TokenStream2 {
+    let output_ident = &model.output_ident;
+    if !model.is_generic() {
+        return quote! {
+            #[allow(non_camel_case_types)]
+            pub struct #output_ident;
+        };
+    }
+
+    // The bounds are left to the implementations, lifetimes being rejected by the validation
+    let params = model.generics.params.iter().map(|param| match param {
+        syn::GenericParam::Type(param) => {
+            let ident = &param.ident;
+            quote! { #ident }
+        }
+        syn::GenericParam::Const(param) => {
+            let ident = &param.ident;
+            let ty = &param.ty;
+            quote! { const #ident: #ty }
+        }
+        syn::GenericParam::Lifetime(_) => quote! {},
+    });
+    let params = quote! { #(#params),* };
+    let args = model::generic_args(&model.generics);
+    let types: Vec<_> = model
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let input_ident = &model.input_ident;
+    let doc = format!(
+        "Instantiates the generic method `{input_ident}` under a name, e.g. `{output_ident}::<..>::named(\"{input_ident}\")`"
+    );
+
+    quote! {
+        #[allow(non_camel_case_types)]
+        pub struct #output_ident<#params> {
+            name: &'static str,
+            _marker: ::std::marker::PhantomData<fn() -> (#(#types,)*)>,
+        }
+
+        impl<#params> #output_ident<#(#args),*> {
+            #[doc = #doc]
+            pub fn named(name: &'static str) -> Self {
+                #output_ident {
+                    name,
+                    _marker: ::std::marker::PhantomData,
+                }
+            }
+        }
+    }
+}
+
+fn gen_fn_name(model: &RpcMethod) -> TokenStream2 {
+    let name = model.name_expr();
     quote::quote! {
         fn name(&self) -> &'static str {
-            stringify!(#fn_name)
+            #name
         }
     }
 }
 
-fn gen_impl_requests(model: &RpcMethod) -> TokenStream2 {
+fn gen_impl_requests(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
     #[cfg(not(feature = "client"))]
     {
+        let _ = (model, args);
         quote! {}
     }
 
     #[cfg(feature = "client")]
     {
-        let fn_request = request::generate(&model, RequestImpl::Checked);
-        let fn_request_unchecked = request::generate(&model, RequestImpl::Unchecked);
+        let fn_requests = if args.notification {
+            request::generate(&model, args, RequestImpl::Notification)
+        } else if model.returns_stream {
+            request::generate_subscribe(&model, args)
+        } else {
+            let fn_request = request::generate(&model, args, RequestImpl::Checked);
+            let fn_request_unchecked = request::generate(&model, args, RequestImpl::Unchecked);
+            let fn_batch_item = request::generate_batch_item(&model, args);
+            let fn_paginate = if args.paginated {
+                request::generate_paginate(&model, args)
+            } else {
+                quote! {}
+            };
+            quote! {
+                #fn_request
+                #fn_request_unchecked
+                #fn_batch_item
+                #fn_paginate
+            }
+        };
 
         let output_ident = &model.output_ident;
+        let (impl_generics, ty_generics, where_clause) = model.generics.split_for_impl();
 
         quote! {
-            impl #output_ident {
-                #fn_request
-                #fn_request_unchecked
+            impl #impl_generics #output_ident #ty_generics #where_clause {
+                #fn_requests
             }
         }
     }
@@ -76,8 +182,8 @@ fn gen_impl_requests(model: &RpcMethod) -> TokenStream2 {
 
 fn gen_impl_rpc_method(
     input: &syn::ItemFn,
-    input_ident: &Ident,
     model: &RpcMethod,
+    args: &RpcMethodArgs,
 ) -> TokenStream2 {
     #[cfg(not(feature = "server"))]
     {
@@ -85,20 +191,40 @@ fn gen_impl_rpc_method(
     }
     #[cfg(feature = "server")]
     {
-        let fn_name = gen_fn_name(&input_ident);
-        let fn_spec = spec::generate(&input, &model.output_ident);
-        let fn_handler = handler::generate(&model);
+        let fn_name = gen_fn_name(model);
+        let fn_spec = spec::generate(input, &model.name_expr(), args);
+        let fn_handler = handler::generate(&model, args);
+        let fn_middleware = gen_fn_middleware(args);
 
         let context_ty_owned = &model.context_ty_owned;
         let response_ty = &model.response_ty;
         let output_ident = &model.output_ident;
+        let (impl_generics, ty_generics, where_clause) = model.generics.split_for_impl();
 
         quote! {
-            impl ::openspec_jsonrpsee::RpcMethod<#context_ty_owned, #response_ty> for #output_ident {
+            #[allow(deprecated)] // Registering a deprecated method shouldn't warn, only calling it
+            impl #impl_generics ::openspec_jsonrpsee::RpcMethod<#context_ty_owned, #response_ty> for #output_ident #ty_generics #where_clause {
                 #fn_name
-                #fn_spec
+                // The handler comes first, so the checks it starts with are reported first
                 #fn_handler
+                #fn_spec
+                #fn_middleware
             }
         }
     }
 }
+
+#[cfg(feature = "server")]
+fn gen_fn_middleware(args: &RpcMethodArgs) -> TokenStream2 {
+    let layers = match &args.middleware {
+        None => return quote! {},
+        Some(syn::Expr::Array(array)) => array.elems.iter().collect(),
+        Some(layer) => vec![layer],
+    };
+
+    quote! {
+        fn middleware(&self) -> Vec<::openspec_jsonrpsee::RpcLayer> {
+            vec![#(::openspec_jsonrpsee::RpcLayer::new(#layers)),*]
+        }
+    }
+}
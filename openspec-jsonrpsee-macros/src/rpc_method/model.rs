@@ -1,9 +1,12 @@
-use crate::helpers::{extract_return_type, owned_type_version};
-use heck::AsUpperCamelCase;
+use crate::helpers::{
+    extract_return_type, owned_type_version, page_request_cursor_type, page_types, response_type,
+    rpc_result_ok_type, rpc_stream_item_type,
+};
+use heck::{AsSnakeCase, AsUpperCamelCase};
 use proc_macro_error::abort;
 use proc_macro2::Span;
-use std::panic;
 use syn::{
+    ext::IdentExt,
     punctuated::*,
     spanned::Spanned,
     token::{Async, Comma},
@@ -16,14 +19,505 @@ pub struct RpcMethodArgs {
     #[darling(default)]
     pub client: Option<syn::Path>,
     pub client_field: Option<Expr>,
+    /// Tag names the method is grouped under, e.g. `tags = ["users", "admin"]`
+    #[darling(default)]
+    pub tags: Vec<LitStr>,
+    /// Overrides the summary derived from the first paragraph of the doc comment
+    pub summary: Option<LitStr>,
+    /// URL to additional external documentation for the method
+    pub external_docs: Option<LitStr>,
+    /// Name of the method replacing this one, recorded as `x-replaced-by`
+    pub replaced_by: Option<LitStr>,
+    /// Fire-and-forget method called without an `id` and never responded to
+    #[darling(default)]
+    pub notification: bool,
+    /// RPC middleware layer(s) scoped to the method, e.g. `middleware = AuthLayer` or
+    /// `middleware = [AuthLayer, LogLayer]` (the first being the outermost)
+    pub middleware: Option<Expr>,
+    /// Scope required to call the method, e.g. `auth = "admin"`
+    pub auth: Option<LitStr>,
+    /// Scopes all required to call the method, e.g. `requires = ["users:read", "users:write"]`
+    #[darling(default)]
+    pub requires: Vec<LitStr>,
+    /// Number of calls allowed per period, e.g. `rate_limit = "100/s"` (per `s`, `m` or `h`)
+    pub rate_limit: Option<LitStr>,
+    /// Number of calls handled at the same time, e.g. `max_concurrency = 8`
+    pub max_concurrency: Option<LitInt>,
+    /// Sync method run on the blocking thread pool, e.g. for CPU-heavy work
+    #[darling(default)]
+    pub blocking: bool,
+    /// Longest the (async) method is given to respond, e.g. `timeout = "5s"` (in `ms`, `s`, `m` or `h`)
+    pub timeout: Option<LitStr>,
+    /// Function declaring the errors the method may return, recorded in the spec, e.g.
+    /// `errors = user_errors` with `fn user_errors() -> Vec<openspec_jsonrpsee::spec::Error>`
+    pub errors: Option<syn::Path>,
+    /// Cursor-paginated method returning a `Page`, taking a `PageRequest` as its last argument
+    #[darling(default)]
+    pub paginated: bool,
 }
 
 impl RpcMethodArgs {
     pub fn parse(args: proc_macro::TokenStream) -> Self {
         match syn::parse::<Self>(args) {
             Ok(v) => v,
-            Err(e) => abort!(e.span(), "Incorrect macro arguments: {:#?}", e),
+            Err(e) => abort!(e.span(), "Incorrect macro arguments: {}", e),
+        }
+    }
+
+    /// Parses the arguments of an `#[rpc]` attribute, e.g. on a method of an `impl` block
+    pub fn from_attr(attr: &Attribute) -> Self {
+        let tokens = match &attr.meta {
+            Meta::Path(_) => proc_macro2::TokenStream::new(),
+            Meta::List(list) => list.tokens.clone(),
+            Meta::NameValue(meta) => abort!(meta, "Expected `#[rpc]` or `#[rpc(...)]`"),
+        };
+        match syn::parse2::<Self>(tokens) {
+            Ok(v) => v,
+            Err(e) => abort!(e.span(), "Incorrect macro arguments: {}", e),
+        }
+    }
+
+    /// Returns true if no argument is set
+    pub fn is_empty(&self) -> bool {
+        let RpcMethodArgs {
+            client,
+            client_field,
+            tags,
+            summary,
+            external_docs,
+            replaced_by,
+            notification,
+            middleware,
+            auth,
+            requires,
+            rate_limit,
+            max_concurrency,
+            blocking,
+            timeout,
+            errors,
+            paginated,
+        } = self;
+        client.is_none()
+            && client_field.is_none()
+            && tags.is_empty()
+            && summary.is_none()
+            && external_docs.is_none()
+            && replaced_by.is_none()
+            && !notification
+            && middleware.is_none()
+            && auth.is_none()
+            && requires.is_empty()
+            && rate_limit.is_none()
+            && max_concurrency.is_none()
+            && !blocking
+            && timeout.is_none()
+            && errors.is_none()
+            && !paginated
+    }
+
+    /// Returns the scopes required to call the method, recorded as `x-security`
+    pub fn scopes(&self) -> Vec<String> {
+        let mut scopes: Vec<String> = self.auth.iter().map(LitStr::value).collect();
+        for scope in &self.requires {
+            if !scopes.contains(&scope.value()) {
+                scopes.push(scope.value());
+            }
+        }
+        scopes
+    }
+
+    /// Formats the required scopes (if any) as a list of inline code, e.g. `` `admin`, `users:write` ``
+    pub fn scopes_list(&self) -> Option<String> {
+        let scopes = self.scopes();
+        (!scopes.is_empty()).then(|| {
+            scopes
+                .iter()
+                .map(|scope| format!("`{scope}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+
+    /// Checks the arguments are applicable to the function they're used on
+    pub fn validate(&self, input: &ItemFn) {
+        if self.notification
+            && let ReturnType::Type(_, ty) = &input.sig.output
+            && !matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+        {
+            abort!(
+                ty,
+                "Notifications can't return a value";
+                help = "remove the return type, a notification is never responded to"
+            );
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            let value = rate_limit.value();
+            let valid = value.split_once('/').is_some_and(|(calls, unit)| {
+                calls.trim().parse::<u32>().is_ok_and(|calls| calls > 0)
+                    && matches!(unit.trim(), "s" | "m" | "h")
+            });
+            if !valid {
+                abort!(
+                    rate_limit,
+                    "Invalid rate limit `{}`", value;
+                    help = "expected a number of calls per `s`, `m` or `h`, e.g. \"100/s\""
+                );
+            }
+        }
+        if let Some(max_concurrency) = &self.max_concurrency
+            && !max_concurrency
+                .base10_parse::<u32>()
+                .is_ok_and(|max_concurrency| max_concurrency > 0)
+        {
+            abort!(
+                max_concurrency,
+                "The max concurrency must be a positive number"
+            );
+        }
+        if let Some(lifetime) = input.sig.generics.lifetimes().next() {
+            abort!(
+                lifetime,
+                "RPC methods can't have lifetime parameters";
+                help = "params are deserialized as owned values"
+            );
+        }
+        if let Some(client) = &self.client
+            && !input.sig.generics.params.is_empty()
+        {
+            abort!(
+                client,
+                "Generic methods can't have a client method";
+                help = "call an instance instead, e.g. `List::<User>::named(\"users.list\").request(&client)`"
+            );
+        }
+        if self.blocking && input.sig.asyncness.is_some() {
+            abort!(
+                input.sig.asyncness,
+                "Async methods can't be blocking";
+                help = "remove `blocking`, or make the function sync to run it on the blocking thread pool"
+            );
+        }
+        if let Some(timeout) = &self.timeout {
+            if input.sig.asyncness.is_none() {
+                abort!(
+                    timeout,
+                    "Only async methods can have a timeout";
+                    help = "make the function async, a sync handler can't be interrupted"
+                );
+            }
+            let value = timeout.value();
+            let valid = value
+                .find(|c: char| !c.is_ascii_digit())
+                .is_some_and(|split| {
+                    let (value, unit) = value.split_at(split);
+                    value.parse::<u64>().is_ok_and(|value| value > 0)
+                        && matches!(unit.trim(), "ms" | "s" | "m" | "h")
+                });
+            if !valid {
+                abort!(
+                    timeout,
+                    "Invalid timeout `{}`", value;
+                    help = "expected a non-zero duration in `ms`, `s`, `m` or `h`, e.g. \"5s\""
+                );
+            }
+        }
+        if let Some(arg) = extract_cancellation_arg(input)
+            && input.sig.asyncness.is_none()
+        {
+            abort!(
+                arg,
+                "Only async methods can take a cancellation token";
+                help = "make the function async, a sync handler can't be cancelled"
+            );
+        }
+        for scope in self.auth.iter().chain(&self.requires) {
+            if scope.value().trim().is_empty() {
+                abort!(scope, "Scopes can't be empty");
+            }
+        }
+        if returns_stream(input) {
+            self.validate_stream(input);
+        }
+        validate_args(input);
+        self.validate_page(input);
+    }
+
+    /// Checks a paginated method returns a `Page` and takes a `PageRequest` as its last param, and
+    /// only paginated methods take one
+    fn validate_page(&self, input: &ItemFn) {
+        let params: Vec<(usize, &PatType)> = input
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                FnArg::Typed(pat_type) if !pat_type.attrs.iter().any(is_injected_arg_attr) => {
+                    Some(pat_type)
+                }
+                _ => None,
+            })
+            .enumerate()
+            .collect();
+        let page_position = params
+            .iter()
+            .position(|(_, pat_type)| page_request_cursor_type(&pat_type.ty).is_some());
+
+        if !self.paginated {
+            if let Some(page_position) = page_position {
+                abort!(
+                    params[page_position].1.ty,
+                    "Only paginated methods take a `PageRequest`";
+                    help = "mark the method `#[rpc(paginated)]`, sending the page request as the `cursor` and `limit` params"
+                );
+            }
+            return;
+        }
+        if page_types(&response_type(&extract_return_type(input))).is_none()
+            || returns_stream(input)
+        {
+            abort!(
+                input.sig.output,
+                "Paginated methods must return a `Page`";
+                help = "return the items with the cursor of the next page, e.g. `-> Page<User>`"
+            );
+        }
+        match page_position {
+            Some(page_position) if page_position + 1 == params.len() => {}
+            Some(page_position) => abort!(
+                params[page_position].1.ty,
+                "The `PageRequest` must be the last param";
+                help = "it's sent as the `cursor` and `limit` params, after the others"
+            ),
+            None => abort!(
+                input.sig.ident,
+                "Paginated methods must take a `PageRequest` as their last param";
+                help = "add a `page: PageRequest` argument, sent as the `cursor` and `limit` params"
+            ),
+        }
+        for (position, pat_type) in &params[..params.len() - 1] {
+            let name = param_name(pat_type, *position);
+            if name == "cursor" || name == "limit" {
+                abort!(
+                    pat_type.pat,
+                    "The param `{}` is taken by the page request", name;
+                    help = "rename the param, e.g. with `#[param(name = \"...\")]`"
+                );
+            }
+        }
+    }
+
+    /// Checks the arguments are applicable to a method streaming its result to a subscriber
+    fn validate_stream(&self, input: &ItemFn) {
+        if self.blocking {
+            abort!(
+                input.sig.output,
+                "Streaming methods can't be blocking";
+                help = "produce the items from the blocking thread pool, e.g. through a channel fed by `spawn_blocking`"
+            );
+        }
+        if let Some(timeout) = &self.timeout {
+            abort!(
+                timeout,
+                "Streaming methods can't have a timeout";
+                help = "the subscription lasts until the stream ends or the client unsubscribes"
+            );
+        }
+        if let Some(arg) = extract_cancellation_arg(input) {
+            abort!(
+                arg,
+                "Streaming methods can't take a cancellation token";
+                help = "the stream is dropped once the client unsubscribes"
+            );
+        }
+    }
+}
+
+/// Returns true if the input returns an `RpcStream` (or an `RpcResult` of one)
+fn returns_stream(input: &ItemFn) -> bool {
+    let ty = extract_return_type(input);
+    let ty = rpc_result_ok_type(&ty).unwrap_or(ty);
+    rpc_stream_item_type(&ty).is_some()
+}
+
+/// Checks the signature can be called by a handler
+fn validate_signature(sig: &Signature) {
+    if let Some(unsafety) = &sig.unsafety {
+        abort!(
+            unsafety,
+            "RPC methods can't be unsafe";
+            help = "call the unsafe code from a safe function, upholding its invariants there"
+        );
+    }
+    if let Some(abi) = &sig.abi {
+        abort!(abi, "RPC methods can't have an explicit ABI");
+    }
+    if let Some(variadic) = &sig.variadic {
+        abort!(variadic, "RPC methods can't be variadic");
+    }
+    if let ReturnType::Type(_, ty) = &sig.output
+        && let Some(impl_trait) = find_impl_trait(ty)
+    {
+        abort!(
+            impl_trait,
+            "RPC methods can't return `impl Trait`";
+            help = "return a concrete type, the result is serialized and described in the spec"
+        );
+    }
+}
+
+/// Checks the arguments can be deserialized as params, or injected, and their names are unique
+fn validate_args(input: &ItemFn) {
+    validate_signature(&input.sig);
+    let mut names: Vec<String> = Vec::new();
+    let mut context: Option<&PatType> = None;
+    let mut cancellation: Option<&PatType> = None;
+    // The position among the params, the injected arguments not being sent
+    let mut position = 0;
+    for arg in input.sig.inputs.iter() {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let injected: Vec<&Attribute> = pat_type
+            .attrs
+            .iter()
+            .filter(|attr| is_injected_arg_attr(attr))
+            .collect();
+        if let [_, second, ..] = injected[..] {
+            abort!(second, "An argument can only be injected once");
+        }
+        if let Some(attr) = injected.first() {
+            let (seen, what, help) = if attr.path().is_ident("context") {
+                (
+                    &mut context,
+                    "the `#[context]`",
+                    "put everything the method needs into one context type, e.g. a struct of the shared state",
+                )
+            } else {
+                (
+                    &mut cancellation,
+                    "the `#[cancellation]` token",
+                    "clone the token to share it",
+                )
+            };
+            if seen.is_some() {
+                abort!(attr, "Only one argument can be {}", what; help = help);
+            }
+            *seen = Some(pat_type);
         }
+        if let Some(context) = context
+            && std::ptr::eq(context, pat_type)
+            && let Type::Reference(reference) = &*pat_type.ty
+            && let Some(mutability) = &reference.mutability
+        {
+            abort!(
+                mutability,
+                "The context is shared by the calls, so it can't be borrowed mutably";
+                help = "use interior mutability, e.g. a `Mutex` in the context"
+            );
+        }
+        if let Some(impl_trait) = find_impl_trait(&pat_type.ty) {
+            abort!(
+                impl_trait,
+                "`impl Trait` arguments can't be deserialized";
+                help = "use a concrete type, or a type parameter instantiated with e.g. `List::<User>::named(\"users.list\")`"
+            );
+        }
+        if pat_type.attrs.iter().any(is_injected_arg_attr) {
+            if !matches!(&*pat_type.pat, Pat::Ident(_)) {
+                abort!(
+                    pat_type.pat,
+                    "Injected arguments must be bound to a name";
+                    help = "destructure the argument in the body of the function instead"
+                );
+            }
+            if let Some(attr) = pat_type
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("param"))
+            {
+                abort!(attr, "`#[param]` can't be used on an injected argument");
+            }
+            continue;
+        }
+
+        if let Type::Reference(reference) = &*pat_type.ty
+            && let Some(mutability) = &reference.mutability
+        {
+            abort!(
+                mutability,
+                "Params can't be borrowed mutably";
+                help = "take the param by value, e.g. `mut items: Vec<u32>`"
+            );
+        }
+        if let Some(reference) = find_nested_reference(&pat_type.ty) {
+            abort!(
+                reference,
+                "Params can only be borrowed as a whole";
+                help = "a borrowed string can't hold an escaped string, take an owned type, e.g. `Option<String>` instead of `Option<&str>`"
+            );
+        }
+
+        let name = param_name(pat_type, position);
+        position += 1;
+        if names.contains(&name) {
+            abort!(
+                pat_type,
+                "Duplicate param name `{}`", name;
+                help = "name the param with `#[param(name = \"...\")]`"
+            );
+        }
+        names.push(name);
+    }
+}
+
+/// Returns the first `impl Trait` in the type, e.g. in `impl Display` or `Vec<impl Display>`
+fn find_impl_trait(ty: &Type) -> Option<proc_macro2::TokenStream> {
+    fn find(tokens: proc_macro2::TokenStream) -> Option<proc_macro2::TokenStream> {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                proc_macro2::TokenTree::Ident(ident) if ident == "impl" => {
+                    let bound = tokens.next();
+                    return Some(quote::quote! { #ident #bound });
+                }
+                proc_macro2::TokenTree::Group(group) => {
+                    if let Some(found) = find(group.stream()) {
+                        return Some(found);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    find(quote::ToTokens::to_token_stream(ty))
+}
+
+/// Returns the first reference inside the type, e.g. the `&str` of `Option<&str>` or `&[&str]`,
+/// the outer reference of a param being deserialized into its owned type
+fn find_nested_reference(ty: &Type) -> Option<&TypeReference> {
+    fn find(ty: &Type) -> Option<&TypeReference> {
+        match ty {
+            Type::Reference(reference) => Some(reference),
+            Type::Array(array) => find(&array.elem),
+            Type::Slice(slice) => find(&slice.elem),
+            Type::Paren(paren) => find(&paren.elem),
+            Type::Group(group) => find(&group.elem),
+            Type::Tuple(tuple) => tuple.elems.iter().find_map(find),
+            Type::Path(path) => path.path.segments.iter().find_map(|segment| {
+                let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                    return None;
+                };
+                args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => find(ty),
+                    _ => None,
+                })
+            }),
+            _ => None,
+        }
+    }
+    match ty {
+        Type::Reference(reference) => find(&reference.elem),
+        _ => find(ty),
     }
 }
 
@@ -34,6 +528,8 @@ pub struct RpcMethod {
     pub input_ident: Ident,
     pub input_vis: Visibility,
     pub output_ident: Ident, // gen_name
+    /// Path the handler calls the input with, e.g. `get_user` or `<MyService>::get_user`
+    pub fn_path: proc_macro2::TokenStream,
     #[allow(dead_code)]
     /// True if input used #[context]
     pub context_needed: bool,
@@ -46,16 +542,29 @@ pub struct RpcMethod {
     /// Owned version of context_ty, e.g. &str --> String
     pub context_ty_owned: Type,
     pub context_ident: Option<Ident>,
+    /// Ident of the argument marked `#[cancellation]`, injected with the call's cancellation token
+    pub cancellation_ident: Option<Ident>,
     #[allow(dead_code)]
     /// fn args e.g. `a: String, b: u32, c: Struct`
     pub fn_args: Punctuated<PatType, Comma>,
     /// fn args as idents E.g. `a, b, c`
     pub fn_args_as_ident: Punctuated<Ident, Comma>,
-    /// fn args without context e.g. `a: String, b: u32, c: Struct`
+    /// fn args without injected args (context, cancellation) e.g. `a: String, b: u32, c: Struct`
     pub fn_args_contextless: Punctuated<PatType, Comma>,
-    /// fn args without context as idents E.g. `a, b, c`
+    /// fn args without injected args (context, cancellation) as idents E.g. `a, b, c`
     pub fn_args_contextless_as_ident: Punctuated<Ident, Comma>,
+    /// Type of the result, e.g. `T` when the input returns `RpcResult<T>` or `RpcStream<T>`
     pub response_ty: Type,
+    /// True if the input returns an `RpcResult`, passed through by the handler to respond with its errors
+    pub returns_rpc_result: bool,
+    /// True if the input returns an `RpcStream`, the method being registered as a subscription
+    pub returns_stream: bool,
+    /// Ident of the `PageRequest` argument of a paginated method, sent as the `cursor` and `limit` params
+    pub page_ident: Option<Ident>,
+    /// The `#[deprecated]` attribute of the input, copied onto generated client functions
+    pub deprecated: Option<Attribute>,
+    /// Generics of the input; a generic method is instantiated under a name, e.g. `List::<User>::named("users.list")`
+    pub generics: Generics,
 }
 
 impl RpcMethod {
@@ -65,11 +574,25 @@ impl RpcMethod {
         let context_ty = context_ty.unwrap_or_else(|| syn::parse_quote!(()));
         let fn_args = extract_fn_args(&input, false);
         let fn_args_contextless = extract_fn_args(&input, true);
+        let page_ident = fn_args_contextless
+            .iter()
+            .zip(as_ident(&fn_args_contextless))
+            .find(|(pat_type, _)| page_request_cursor_type(&pat_type.ty).is_some())
+            .map(|(_, ident)| ident);
 
         RpcMethod {
             input_async: input.sig.asyncness,
             input_span: input.span(),
             input_ident: input.sig.ident.clone(),
+            fn_path: {
+                let ident = &input.sig.ident;
+                let args = generic_args(&input.sig.generics);
+                if args.is_empty() {
+                    quote::quote! { #ident }
+                } else {
+                    quote::quote! { #ident::<#(#args),*> }
+                }
+            },
             input_vis: input.vis.clone(),
             output_ident: Ident::new(
                 &AsUpperCamelCase(input.sig.ident.to_string()).to_string(),
@@ -81,13 +604,69 @@ impl RpcMethod {
             context_ty_owned: owned_type_version(&context_ty),
             context_ty,
             context_ident: extract_context_ident(&input),
+            cancellation_ident: extract_cancellation_arg(&input).and_then(|pat_type| {
+                match &*pat_type.pat {
+                    syn::Pat::Ident(syn::PatIdent { ident, .. }) => Some(ident.clone()),
+                    _ => None,
+                }
+            }),
             fn_args_as_ident: as_ident(&fn_args),
             fn_args,
             fn_args_contextless_as_ident: as_ident(&fn_args_contextless),
             fn_args_contextless,
-            response_ty: extract_return_type(&input),
+            response_ty: response_type(&extract_return_type(&input)),
+            returns_rpc_result: rpc_result_ok_type(&extract_return_type(&input)).is_some(),
+            returns_stream: returns_stream(&input),
+            page_ident,
+            deprecated: input
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("deprecated"))
+                .cloned(),
+            generics: input.sig.generics.clone(),
+        }
+    }
+
+    /// Returns true if the method has type or const parameters
+    pub fn is_generic(&self) -> bool {
+        !self.generics.params.is_empty()
+    }
+
+    /// Returns an expression of the method's name: its ident, or the name of the instance if generic
+    pub fn name_expr(&self) -> proc_macro2::TokenStream {
+        let input_ident = &self.input_ident;
+        if self.is_generic() {
+            quote::quote! { self.name }
+        } else {
+            quote::quote! { stringify!(#input_ident) }
         }
     }
+
+    /// Returns the expressions of the params sent by the client, the `PageRequest` of a paginated
+    /// method being sent as its `cursor` and `limit`
+    pub fn wire_params(&self) -> proc_macro2::TokenStream {
+        let params = self.fn_args_contextless_as_ident.iter().map(|ident| {
+            if self.page_ident.as_ref() == Some(ident) {
+                quote::quote! { #ident.cursor, #ident.limit }
+            } else {
+                quote::quote! { #ident }
+            }
+        });
+        quote::quote! { #(#params),* }
+    }
+}
+
+/// Returns the idents of the type and const parameters, e.g. `T, N` of `<T: Entity, const N: usize>`
+pub fn generic_args(generics: &Generics) -> Vec<&Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect()
 }
 
 pub fn extract_context_arg(input: &syn::ItemFn) -> Option<&syn::PatType> {
@@ -103,6 +682,25 @@ pub fn extract_context_arg(input: &syn::ItemFn) -> Option<&syn::PatType> {
     })
 }
 
+/// Returns true for the attributes marking arguments injected by the server instead of being params
+pub fn is_injected_arg_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("context") || attr.path().is_ident("cancellation")
+}
+
+pub fn extract_cancellation_arg(input: &syn::ItemFn) -> Option<&syn::PatType> {
+    input.sig.inputs.iter().find_map(|arg| match arg {
+        syn::FnArg::Typed(pat_type)
+            if pat_type
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("cancellation")) =>
+        {
+            Some(pat_type)
+        }
+        _ => None,
+    })
+}
+
 pub fn extract_context_ident(input: &syn::ItemFn) -> Option<syn::Ident> {
     for arg in &input.sig.inputs {
         if let syn::FnArg::Typed(pat_type) = arg {
@@ -119,31 +717,34 @@ pub fn extract_context_ident(input: &syn::ItemFn) -> Option<syn::Ident> {
 }
 
 fn extract_fn_args(input: &syn::ItemFn, exclude_context: bool) -> Punctuated<syn::PatType, Comma> {
+    // The position among the params, the injected arguments not being sent
+    let mut position = 0;
     input
         .sig
         .inputs
         .iter()
         .filter_map(|arg| match arg {
-            syn::FnArg::Receiver(_) => panic!("function cannot take self"),
+            syn::FnArg::Receiver(receiver) => abort!(
+                receiver,
+                "Function cannot take self";
+                help = "put the method in an `impl` block marked `#[rpc]`, where `&self` is the context"
+            ),
             syn::FnArg::Typed(pat_type) => {
-                // If exclude_context is true and this arg has #[context], skip it entirely
-                if exclude_context
-                    && pat_type
-                        .attrs
-                        .iter()
-                        .any(|attr| attr.path().is_ident("context"))
-                {
+                // If exclude_context is true and this arg is injected (#[context], #[cancellation]), skip it entirely
+                let injected = pat_type.attrs.iter().any(is_injected_arg_attr);
+                if exclude_context && injected {
                     return None;
                 }
                 let mut pat_type = pat_type.clone();
-                // Remove #[context] attribute if present
-                pat_type
-                    .attrs
-                    .retain(|attr| !attr.path().is_ident("context"));
-                // Remove `mut` for argument generation
-                if let syn::Pat::Ident(pat_ident) = &mut *pat_type.pat {
-                    pat_ident.mutability = None;
+                // Remove #[context]/#[cancellation]/#[param] attributes if present
+                pat_type.attrs.retain(|attr| !is_arg_attr(attr));
+                // Bind the argument to a plain ident for argument generation, the input fn
+                // destructuring it (if it's a pattern) and making it `mut` (if needed) itself
+                let ident = param_ident(&pat_type, position);
+                if !injected {
+                    position += 1;
                 }
+                *pat_type.pat = parse_quote! { #ident };
                 Some(pat_type)
             }
         })
@@ -153,12 +754,71 @@ fn extract_fn_args(input: &syn::ItemFn, exclude_context: bool) -> Punctuated<syn
 pub fn as_ident(fn_args: &Punctuated<syn::PatType, Comma>) -> Punctuated<syn::Ident, Comma> {
     fn_args
         .iter()
-        .filter_map(|arg| {
-            if let syn::Pat::Ident(pat_ident) = arg.pat.as_ref() {
-                Some(pat_ident.ident.clone())
-            } else {
-                panic!("Unexpected pattern type")
-            }
-        })
+        .enumerate()
+        .map(|(position, arg)| param_ident(arg, position))
         .collect()
 }
+
+/// Returns true for the attributes of arguments handled by the macro, removed from the input
+pub fn is_arg_attr(attr: &Attribute) -> bool {
+    is_injected_arg_attr(attr) || attr.path().is_ident("param")
+}
+
+/// Returns the name given with `#[param(name = "...")]`, if any
+fn param_attr_name(pat_type: &PatType) -> Option<LitStr> {
+    let attr = pat_type
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("param"))?;
+    let mut name = None;
+    let parsed = attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"...\"`"))
+        }
+    });
+    if let Err(error) = parsed {
+        abort!(error.span(), "Invalid `#[param]` attribute: {}", error);
+    }
+    match name {
+        Some(name) if !name.value().trim().is_empty() => Some(name),
+        Some(name) => abort!(name, "Param names can't be empty"),
+        None => abort!(attr, "Expected `#[param(name = \"...\")]`"),
+    }
+}
+
+/// Returns the name of the param in the spec: the name given with `#[param(name = "...")]`, the
+/// ident of the argument (without `r#`), or a synthetic name for a destructured argument, e.g. `point`
+/// for `Point { x, y }: Point` and `arg1` for the second param `(a, b): (u32, u32)`. The `position`
+/// counts the params only, so injected arguments (`#[context]`, `#[cancellation]`) don't rename
+/// the params after them.
+pub fn param_name(pat_type: &PatType, position: usize) -> String {
+    if let Some(name) = param_attr_name(pat_type) {
+        return name.value();
+    }
+    let path = match &*pat_type.pat {
+        Pat::Ident(pat_ident) => return pat_ident.ident.unraw().to_string(),
+        Pat::Struct(pat) => Some(&pat.path),
+        Pat::TupleStruct(pat) => Some(&pat.path),
+        _ => None,
+    };
+    match path.and_then(|path| path.segments.last()) {
+        Some(segment) => AsSnakeCase(segment.ident.to_string()).to_string(),
+        None => format!("arg{position}"),
+    }
+}
+
+/// Returns the ident the argument is bound to in generated code: the ident of the argument, or for a
+/// destructured argument its param name (`__arg1` if the name isn't an ident)
+pub fn param_ident(pat_type: &PatType, position: usize) -> Ident {
+    if let Pat::Ident(pat_ident) = &*pat_type.pat {
+        return pat_ident.ident.clone();
+    }
+    let span = pat_type.pat.span();
+    match syn::parse_str::<Ident>(&param_name(pat_type, position)) {
+        Ok(ident) => Ident::new(&ident.to_string(), span),
+        Err(_) => Ident::new(&format!("__arg{position}"), span),
+    }
+}
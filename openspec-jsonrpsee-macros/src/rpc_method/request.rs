@@ -1,33 +1,45 @@
-use super::RpcMethod;
+use super::{RpcMethod, RpcMethodArgs, checks};
+use crate::helpers::page_types;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
 use syn::Type;
 
 /// Generates a type safe asynchronous function that calls the input ItemFn
-pub fn generate(
-    RpcMethod {
+pub fn generate(model: &RpcMethod, args: &RpcMethodArgs, impl_kind: RequestImpl) -> TokenStream2 {
+    let RpcMethod {
         input_vis,
         input_ident,
         fn_args_contextless,
-        fn_args_contextless_as_ident,
         response_ty,
+        deprecated,
         ..
-    }: &RpcMethod,
-    impl_kind: RequestImpl,
-) -> TokenStream2 {
+    } = model;
+    let wire_params = model.wire_params();
     let request_ident = impl_kind.name(input_ident.span());
     let actual_response_ty = impl_kind.actual_response_type(&response_ty);
     let return_response = impl_kind.return_response();
     let rust_doc = impl_kind.rust_doc();
+    let call = impl_kind.call(&model.name_expr(), response_ty);
+    let scopes_doc = scopes_doc(args);
+    let receiver = receiver(model);
+    // Checked once per method, `request_unchecked` having the same types as `request`
+    let checks = match impl_kind {
+        RequestImpl::Checked => checks::assertions(&checks::client(model, true)),
+        RequestImpl::Notification => checks::assertions(&checks::client(model, false)),
+        RequestImpl::Unchecked => quote! {},
+    };
 
     quote! {
         #rust_doc
-        #input_vis async fn #request_ident(client: &::jsonrpsee::http_client::HttpClient, #fn_args_contextless) -> #actual_response_ty {
+        #scopes_doc
+        #deprecated
+        #input_vis async fn #request_ident(#receiver client: &impl ::jsonrpsee::core::client::ClientT, #fn_args_contextless) -> #actual_response_ty {
             use ::jsonrpsee::core::client::ClientT as _;
+            #checks
 
-            let params = ::jsonrpsee::rpc_params!(#fn_args_contextless_as_ident);
+            let params = ::jsonrpsee::rpc_params!(#wire_params);
             let response = client
-                .request::<#response_ty, _>(stringify!(#input_ident), params)
+                .#call
                 .await;
 
             #return_response
@@ -35,9 +47,146 @@ pub fn generate(
     }
 }
 
+/// Generates a function creating a type safe call of the input ItemFn for a batch request
+pub fn generate_batch_item(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
+    let RpcMethod {
+        input_vis,
+        fn_args_contextless,
+        response_ty,
+        deprecated,
+        ..
+    } = model;
+    let wire_params = model.wire_params();
+    let scopes_doc = scopes_doc(args);
+    let receiver = receiver(model);
+    let name = model.name_expr();
+
+    quote! {
+        #[doc = "Creates a type safe call for a batch request, see `openspec_jsonrpsee::batch_request`"]
+        #scopes_doc
+        #deprecated
+        #input_vis fn batch_item(#receiver #fn_args_contextless) -> ::openspec_jsonrpsee::BatchItem<#response_ty> {
+            ::openspec_jsonrpsee::BatchItem::new(
+                #name,
+                ::jsonrpsee::rpc_params!(#wire_params),
+            )
+        }
+    }
+}
+
+/// Generates a type safe asynchronous function subscribing to the stream of the input ItemFn
+pub fn generate_subscribe(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
+    let RpcMethod {
+        input_vis,
+        input_ident,
+        fn_args_contextless,
+        response_ty,
+        deprecated,
+        ..
+    } = model;
+    let wire_params = model.wire_params();
+    let subscribe_ident = syn::Ident::new("subscribe", input_ident.span());
+    let scopes_doc = scopes_doc(args);
+    let receiver = receiver(model);
+    let name = model.name_expr();
+    let checks = checks::assertions(&checks::client(model, true));
+
+    quote! {
+        #[doc = "Subscribes to the type safe stream of items with the given client, see `openspec_jsonrpsee::RpcStream`"]
+        #scopes_doc
+        #deprecated
+        #input_vis async fn #subscribe_ident(#receiver client: &impl ::jsonrpsee::core::client::SubscriptionClientT, #fn_args_contextless) -> ::std::result::Result<::jsonrpsee::core::client::Subscription<#response_ty>, ::jsonrpsee::core::ClientError> {
+            use ::jsonrpsee::core::client::SubscriptionClientT as _;
+            #checks
+
+            let params = ::jsonrpsee::rpc_params!(#wire_params);
+            let name = #name;
+            client
+                .subscribe::<#response_ty, _>(name, params, &::openspec_jsonrpsee::unsubscribe_method_name(name))
+                .await
+        }
+    }
+}
+
+/// Generates a type safe function streaming the items of all the pages of the paginated input
+/// ItemFn, following the cursors from the first page
+pub fn generate_paginate(model: &RpcMethod, args: &RpcMethodArgs) -> TokenStream2 {
+    let RpcMethod {
+        input_vis,
+        input_ident,
+        fn_args_contextless,
+        fn_args_contextless_as_ident,
+        response_ty,
+        page_ident,
+        deprecated,
+        ..
+    } = model;
+    let Some((item_ty, cursor_ty)) = page_types(response_ty) else {
+        return quote! {};
+    };
+    let (fn_args, params): (Vec<_>, Vec<_>) = fn_args_contextless
+        .iter()
+        .zip(fn_args_contextless_as_ident)
+        .filter(|(_, ident)| Some(*ident) != page_ident.as_ref())
+        .unzip();
+    let paginate_ident = syn::Ident::new("paginate", input_ident.span());
+    let scopes_doc = scopes_doc(args);
+    let receiver = receiver(model);
+    let name = model.name_expr();
+    let checks = checks::assertions(&checks::client(model, true));
+
+    quote! {
+        #[doc = "Streams the items of all the pages with the given client, requesting up to `limit` items per page, see `openspec_jsonrpsee::paginate`"]
+        #scopes_doc
+        #deprecated
+        #input_vis fn #paginate_ident(#receiver client: &impl ::jsonrpsee::core::client::ClientT, #(#fn_args,)* limit: Option<u32>) -> impl ::openspec_jsonrpsee::macro_support::Stream<Item = ::std::result::Result<#item_ty, ::jsonrpsee::core::ClientError>> {
+            use ::jsonrpsee::core::client::ClientT as _;
+            #checks
+
+            let name = #name;
+            let params = ::jsonrpsee::rpc_params!(#(#params),*);
+            ::openspec_jsonrpsee::paginate(limit, move |page: ::openspec_jsonrpsee::PageRequest<#cursor_ty>| {
+                let mut params = params.clone();
+                let inserted = params.insert(page.cursor).and_then(|()| params.insert(page.limit));
+                async move {
+                    inserted.map_err(::jsonrpsee::core::ClientError::ParseError)?;
+                    client
+                        .request::<::openspec_jsonrpsee::Page<#item_ty, #cursor_ty>, _>(name, params)
+                        .await
+                }
+            })
+        }
+    }
+}
+
+/// Instances of generic methods hold their name, so their calls take `&self`
+fn receiver(model: &RpcMethod) -> TokenStream2 {
+    if model.is_generic() {
+        quote! { &self, }
+    } else {
+        quote! {}
+    }
+}
+
+/// Generates a doc paragraph listing the scopes required to call the method (if any)
+pub fn scopes_doc(args: &RpcMethodArgs) -> TokenStream2 {
+    match args.scopes_list() {
+        Some(scopes) => {
+            let doc = format!("Requires the scopes: {scopes}");
+            quote! {
+                #[doc = ""]
+                #[doc = #doc]
+            }
+        }
+        None => quote! {},
+    }
+}
+
 pub enum RequestImpl {
     Checked,
     Unchecked,
+    /// Sends a notification, i.e. a request without an `id` that is never responded to
+    Notification,
 }
 
 impl RequestImpl {
@@ -46,6 +195,7 @@ impl RequestImpl {
             match self {
                 RequestImpl::Unchecked => "request_unchecked",
                 RequestImpl::Checked => "request",
+                RequestImpl::Notification => "notify",
             },
             fn_span,
         )
@@ -57,6 +207,20 @@ impl RequestImpl {
             RequestImpl::Checked => {
                 quote! { ::std::result::Result<#response_ty, ::jsonrpsee::core::ClientError> }
             }
+            RequestImpl::Notification => {
+                quote! { ::std::result::Result<(), ::jsonrpsee::core::ClientError> }
+            }
+        }
+    }
+
+    pub fn call(&self, method_name: &TokenStream2, response_ty: &Type) -> TokenStream2 {
+        match self {
+            RequestImpl::Checked | RequestImpl::Unchecked => {
+                quote! { request::<#response_ty, _>(#method_name, params) }
+            }
+            RequestImpl::Notification => {
+                quote! { notification(#method_name, params) }
+            }
         }
     }
 
@@ -65,7 +229,7 @@ impl RequestImpl {
             RequestImpl::Unchecked => {
                 quote! { response.expect("RPC call failed but should need successful response") }
             }
-            RequestImpl::Checked => quote! { response },
+            RequestImpl::Checked | RequestImpl::Notification => quote! { response },
         }
     }
 
@@ -77,6 +241,9 @@ impl RequestImpl {
             RequestImpl::Checked => {
                 quote! { #[doc = "Makes a type safe RPC request with the given client"] }
             }
+            RequestImpl::Notification => {
+                quote! { #[doc = "Sends a type safe RPC notification with the given client. No response is awaited."] }
+            }
         }
     }
 }
@@ -1,29 +1,18 @@
-use syn::ItemFn;
+use super::model::is_arg_attr;
+use syn::{FnArg, ItemFn, punctuated::Punctuated, token::Comma};
 
-pub fn generate(input: &syn::ItemFn) -> syn::ItemFn {
+pub fn generate(input: &ItemFn) -> ItemFn {
     let mut input = input.clone();
-    fn_arg_drop_attr_context(&mut input);
+    fn_arg_drop_attrs(&mut input.sig.inputs);
     input
 }
 
-/// remove #[context] on function args (if any)
-fn fn_arg_drop_attr_context(input: &mut ItemFn) {
-    let mut new_inputs = syn::punctuated::Punctuated::new();
-    for arg in input.sig.inputs.iter() {
-        let arg = match arg {
-            syn::FnArg::Typed(pat_type) => {
-                let mut pat_type = pat_type.clone();
-                pat_type
-                    .attrs
-                    .retain(|attr| !attr.path().is_ident("context"));
-                syn::FnArg::Typed(pat_type)
-            }
-            syn::FnArg::Receiver(receiver) => {
-                // syn::Receiver does have attrs, but we keep as-is
-                syn::FnArg::Receiver(receiver.clone())
-            }
-        };
-        new_inputs.push(arg);
+/// remove #[context], #[cancellation] and #[param] on function args (if any)
+pub fn fn_arg_drop_attrs(inputs: &mut Punctuated<FnArg, Comma>) {
+    for arg in inputs.iter_mut() {
+        // syn::Receiver does have attrs, but we keep as-is
+        if let FnArg::Typed(pat_type) = arg {
+            pat_type.attrs.retain(|attr| !is_arg_attr(attr));
+        }
     }
-    input.sig.inputs = new_inputs;
 }
@@ -1,34 +1,49 @@
+use super::RpcMethodArgs;
+use crate::helpers::{
+    extract_return_type, owned_type_version, page_request_cursor_type, response_type,
+};
+use heck::AsUpperCamelCase;
 use proc_macro_error::abort;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
-    Expr, FnArg, Ident, ItemFn, Lit, Pat, PatIdent, PatType, ReturnType, Type, parse_quote,
+    Expr, ExprLit, FnArg, ItemFn, Lit, LitStr, Meta, MetaNameValue, PatType, Type, parse_quote,
     punctuated::Punctuated, token::Comma,
 };
 
-pub fn generate(input: &syn::ItemFn, output_ident: &Ident) -> TokenStream {
-    let name = input.sig.ident.to_string();
-    let description = extract_description(input);
+/// Generates the `spec` fn, `name` being an expression of the method's name
+pub fn generate(input: &syn::ItemFn, name: &TokenStream, args: &RpcMethodArgs) -> TokenStream {
+    let (summary, description) = extract_summary_and_description(input, args);
+    let extensions = extract_extensions(args);
+    let tags = extract_tags(args);
+    let external_docs = extract_external_docs(args);
     let deprecated = extract_deprecated(input);
+    let errors = extract_errors(args);
     let params = extract_params(input);
-    let result = extract_result(input, output_ident);
+    let links = extract_links(input, name, args);
+    let result = if args.notification {
+        quote! { None } // OpenRPC: a method without a result is only used as a notification
+    } else {
+        extract_result(input, name)
+    };
 
     quote! {
         fn spec(&self) -> ::openspec_jsonrpsee::spec::Method {
             ::openspec_jsonrpsee::spec::Method {
-                name: #name.into(),
-                tags: None,
-                summary: None,
+                name: ::std::string::String::from(#name),
+                tags: #tags,
+                summary: #summary,
                 description: #description,
-                external_docs: None,
+                external_docs: #external_docs,
                 params: vec![#(#params),*],
                 result: #result,
                 deprecated: #deprecated,
                 servers: None,
-                errors: None,
-                links: None,
+                errors: #errors,
+                links: #links,
                 param_structure: None,
                 examples: None,
+                extensions: #extensions,
             }
         }
     }
@@ -42,8 +57,175 @@ fn extract_deprecated(input: &ItemFn) -> TokenStream {
     quote! { Some(#is_deprecated) }
 }
 
-fn extract_description(input: &ItemFn) -> TokenStream {
-    let doc_lines = input
+/// Splits the doc comment into a summary (first paragraph) and description (the rest).
+/// When the summary is given explicitly by the macro arguments, the whole doc comment is the description.
+/// A note describing the `#[deprecated]` attribute is appended to the description.
+fn extract_summary_and_description(
+    input: &ItemFn,
+    args: &RpcMethodArgs,
+) -> (TokenStream, TokenStream) {
+    let doc_lines = extract_doc_lines(input);
+    let doc_lines: Vec<String> = doc_lines
+        .into_iter()
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+
+    let (summary, description) = match &args.summary {
+        Some(summary) => (Some(summary.value()), doc_lines.join("\n")),
+        None => {
+            let split = doc_lines
+                .iter()
+                .position(|line| line.trim().is_empty())
+                .unwrap_or(doc_lines.len());
+            let (first, rest) = doc_lines.split_at(split);
+            let summary = first.join(" ");
+            ((!summary.is_empty()).then_some(summary), rest.join("\n"))
+        }
+    };
+    let description = match extract_deprecation_note(input, args) {
+        Some(note) if description.trim().is_empty() => note,
+        Some(note) => format!("{}\n\n{note}", description.trim()),
+        None => description,
+    };
+    let description = match scopes_note(args) {
+        Some(note) if description.trim().is_empty() => note,
+        Some(note) => format!("{}\n\n{note}", description.trim()),
+        None => description,
+    };
+    let description = description.trim();
+
+    let summary = match summary {
+        Some(summary) => quote! { Some(String::from(#summary)) },
+        None => quote! { None },
+    };
+    let description = if description.is_empty() {
+        quote! { None }
+    } else {
+        quote! { Some(String::from(#description)) }
+    };
+
+    (summary, description)
+}
+
+/// Describes the `note`/`since` of the `#[deprecated]` attribute (if any) for the description.
+fn extract_deprecation_note(input: &ItemFn, args: &RpcMethodArgs) -> Option<String> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("deprecated"))?;
+
+    let mut since = None;
+    let mut note = None;
+    match &attr.meta {
+        Meta::Path(_) => {}
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }),
+            ..
+        }) => note = Some(lit.value()),
+        Meta::NameValue(meta) => abort!(meta.value, "Expected a string literal as the note"),
+        Meta::List(list) => {
+            let parsed = list.parse_nested_meta(|meta| {
+                let value: LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("since") {
+                    since = Some(value.value());
+                } else if meta.path.is_ident("note") {
+                    note = Some(value.value());
+                } else {
+                    return Err(meta.error("Expected `since` or `note`"));
+                }
+                Ok(())
+            });
+            if let Err(e) = parsed {
+                abort!(e.span(), "Invalid #[deprecated] attribute: {}", e);
+            }
+        }
+    }
+
+    let mut deprecation = String::from("**Deprecated**");
+    if let Some(since) = since {
+        deprecation.push_str(&format!(" since {since}"));
+    }
+    if let Some(note) = note {
+        deprecation.push_str(&format!(": {note}"));
+    }
+    if let Some(replaced_by) = &args.replaced_by {
+        deprecation.push_str(&format!(" (replaced by `{}`)", replaced_by.value()));
+    }
+    Some(deprecation)
+}
+
+/// Describes the scopes required by `auth`/`requires` (if any) for the description.
+fn scopes_note(args: &RpcMethodArgs) -> Option<String> {
+    args.scopes_list()
+        .map(|scopes| format!("**Requires** the scopes: {scopes}"))
+}
+
+fn extract_extensions(args: &RpcMethodArgs) -> TokenStream {
+    let mut extensions = Vec::new();
+    if let Some(replaced_by) = &args.replaced_by {
+        extensions.push(quote! {
+            (
+                String::from(::openspec_jsonrpsee::spec::extension::REPLACED_BY),
+                ::jsonrpsee::core::JsonValue::from(#replaced_by),
+            )
+        });
+    }
+
+    let scopes = args.scopes();
+    if !scopes.is_empty() {
+        extensions.push(quote! {
+            (
+                String::from(::openspec_jsonrpsee::spec::extension::SECURITY),
+                ::jsonrpsee::core::JsonValue::from(vec![#(#scopes),*]),
+            )
+        });
+    }
+
+    if let Some(rate_limit) = &args.rate_limit {
+        extensions.push(quote! {
+            (
+                String::from(::openspec_jsonrpsee::spec::extension::RATE_LIMIT),
+                ::jsonrpsee::core::JsonValue::from(#rate_limit),
+            )
+        });
+    }
+    if let Some(max_concurrency) = &args.max_concurrency {
+        let max_concurrency = max_concurrency.base10_parse::<u32>().unwrap();
+        extensions.push(quote! {
+            (
+                String::from(::openspec_jsonrpsee::spec::extension::MAX_CONCURRENCY),
+                ::jsonrpsee::core::JsonValue::from(#max_concurrency),
+            )
+        });
+    }
+
+    if let Some(timeout) = &args.timeout {
+        extensions.push(quote! {
+            (
+                String::from(::openspec_jsonrpsee::spec::extension::TIMEOUT),
+                ::jsonrpsee::core::JsonValue::from(#timeout),
+            )
+        });
+    }
+
+    quote! { ::std::collections::HashMap::from([#(#extensions),*]) }
+}
+
+/// Calls the function declaring the errors of the method (if any)
+fn extract_errors(args: &RpcMethodArgs) -> TokenStream {
+    match &args.errors {
+        Some(errors) => quote! {{
+            let errors: Vec<::openspec_jsonrpsee::spec::Error> = #errors();
+            Some(errors)
+        }},
+        None => quote! { None },
+    }
+}
+
+fn extract_doc_lines(input: &ItemFn) -> Vec<String> {
+    input
         .attrs
         .iter()
         .filter_map(|attr| {
@@ -63,76 +245,148 @@ fn extract_description(input: &ItemFn) -> TokenStream {
                 None
             }
         })
-        .collect::<Vec<String>>()
-        .join("\n");
+        .collect()
+}
 
-    if doc_lines.is_empty() {
-        quote! { None }
-    } else {
-        quote! { Some(String::from(#doc_lines)) }
+fn extract_tags(args: &RpcMethodArgs) -> TokenStream {
+    if args.tags.is_empty() {
+        return quote! { None };
+    }
+
+    let tags = args.tags.iter().map(|tag| {
+        quote! {
+            ::openspec_jsonrpsee::spec::ReferenceOr::Item(::openspec_jsonrpsee::spec::Tag {
+                name: String::from(#tag),
+                summary: None,
+                description: None,
+                external_docs: None,
+            })
+        }
+    });
+    quote! { Some(vec![#(#tags),*]) }
+}
+
+fn extract_external_docs(args: &RpcMethodArgs) -> TokenStream {
+    match &args.external_docs {
+        Some(url) => quote! {
+            Some(::openspec_jsonrpsee::spec::ExternalDocumentation {
+                description: None,
+                url: String::from(#url),
+            })
+        },
+        None => quote! { None },
     }
 }
 
 fn extract_params(input: &ItemFn) -> Vec<TokenStream> {
     filtered_params(&input.sig.inputs)
         .into_iter()
-        .map(|param| {
-            let name = match &param {
-                FnArg::Typed(PatType { pat, .. }) => match &**pat {
-                    Pat::Ident(PatIdent { ident, .. }) => quote! { stringify!(#ident).into() },
-                    _ => quote! { None },
-                },
-                FnArg::Receiver(_) => quote! { "self".into() },
-            };
-
-            let schema = match &param {
-                FnArg::Typed(PatType { ty, .. }) => schema_generator(ty),
-                FnArg::Receiver(token) => abort!(token, "Receiver type not supported for schema"),
-            };
+        .flat_map(|(position, pat_type)| {
+            let is_deprecated = pat_type
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("deprecated"));
 
-            let deprecated = match &param {
-                FnArg::Typed(PatType { attrs, .. }) => {
-                    let is_deprecated = attrs.iter().any(|attr| attr.path().is_ident("deprecated"));
-                    quote! { Some(#is_deprecated) }
-                }
-                FnArg::Receiver(_) => quote! { None },
+            // The page request of a paginated method is sent as the `cursor` and `limit` params
+            let params = match page_request_cursor_type(&pat_type.ty) {
+                Some(cursor_ty) => vec![
+                    (
+                        String::from("cursor"),
+                        quote! { Some(String::from("The cursor of the page, `null` for the first page")) },
+                        parse_quote! { Option<#cursor_ty> },
+                    ),
+                    (
+                        String::from("limit"),
+                        quote! { Some(String::from("The number of items requested")) },
+                        parse_quote! { Option<u32> },
+                    ),
+                ],
+                None => vec![(
+                    super::model::param_name(pat_type, position),
+                    quote! { None },
+                    owned_type_version(&pat_type.ty),
+                )],
             };
-
-            quote! {
-                ::openspec_jsonrpsee::spec::ContentDescriptor {
-                    name: #name,
-                    summary: None,
-                    description: None,
-                    required: Some(true),
-                    schema: #schema,
-                    deprecated: #deprecated,
+            params.into_iter().map(move |(name, description, ty): (String, TokenStream, Type)| {
+                let schema = schema_generator(&ty);
+                quote! {
+                    ::openspec_jsonrpsee::spec::ContentDescriptor {
+                        name: String::from(#name),
+                        summary: None,
+                        description: #description,
+                        required: Some(true),
+                        schema: #schema,
+                        deprecated: Some(#is_deprecated),
+                    }
                 }
-            }
+            })
         })
         .collect()
 }
 
-/// Remove context parameters from the input
-fn filtered_params(input: &Punctuated<FnArg, Comma>) -> Vec<FnArg> {
+/// Links a paginated method to its next page, called with the same params and the `next_cursor` of
+/// the result
+fn extract_links(input: &ItemFn, name: &TokenStream, args: &RpcMethodArgs) -> TokenStream {
+    if !args.paginated {
+        return quote! { None };
+    }
+    let params = filtered_params(&input.sig.inputs)
+        .into_iter()
+        .filter(|(_, pat_type)| page_request_cursor_type(&pat_type.ty).is_none())
+        .map(|(position, pat_type)| {
+            let name = super::model::param_name(pat_type, position);
+            (name.clone(), format!("$params.{name}"))
+        })
+        .chain([
+            (String::from("cursor"), String::from("$result.next_cursor")),
+            (String::from("limit"), String::from("$params.limit")),
+        ])
+        .map(|(name, value)| {
+            quote! { (String::from(#name), ::jsonrpsee::core::JsonValue::from(#value)) }
+        });
+
+    quote! {
+        Some(vec![::openspec_jsonrpsee::spec::Link {
+            name: String::from("next_page"),
+            description: Some(String::from("The next page, while the `next_cursor` of the result isn't `null`")),
+            summary: None,
+            method: String::from(#name),
+            params: Some(::std::collections::HashMap::from([#(#params),*])),
+            server: None,
+        }])
+    }
+}
+
+/// Remove injected parameters (context, cancellation) from the input, with the position of each
+/// param among the params
+fn filtered_params(input: &Punctuated<FnArg, Comma>) -> Vec<(usize, &PatType)> {
     input
         .iter()
-        .filter(|param| match param {
-            FnArg::Receiver(_) => true,
-            FnArg::Typed(PatType { attrs, .. }) => {
-                attrs.iter().all(|attr| !attr.path().is_ident("context"))
-            }
+        .filter_map(|param| match param {
+            FnArg::Receiver(token) => abort!(token, "Receiver type not supported for schema"),
+            FnArg::Typed(pat_type) => (!pat_type
+                .attrs
+                .iter()
+                .any(super::model::is_injected_arg_attr))
+            .then_some(pat_type),
         })
-        .cloned()
+        .enumerate()
         .collect()
 }
 
-/// Generate the result spec component of the function
-fn extract_result(input: &ItemFn, output_ident: &Ident) -> TokenStream {
-    let name = format!("{output_ident}Response");
-    let schema = match &input.sig.output {
-        ReturnType::Default => schema_generator(&parse_quote! { () }),
-        ReturnType::Type(_, ty) => schema_generator(ty),
+/// Generate the result spec component of the function, named after the function (not the struct,
+/// prefixed for the methods of a service), the instances of a generic method being told apart by
+/// the name they're instantiated under
+fn extract_result(input: &ItemFn, name: &TokenStream) -> TokenStream {
+    let name = if input.sig.generics.params.is_empty() {
+        let name = format!("{}Response", AsUpperCamelCase(input.sig.ident.to_string()));
+        quote! { String::from(#name) }
+    } else {
+        quote! { ::openspec_jsonrpsee::macro_support::instance_result_name(#name) }
     };
+    // An `RpcResult<T>` is described by `T`, its errors being JSON-RPC errors, and an
+    // `RpcStream<T>` by its items
+    let schema = schema_generator(&response_type(&extract_return_type(input)));
     let is_deprecated = input
         .attrs
         .iter()
@@ -140,7 +394,7 @@ fn extract_result(input: &ItemFn, output_ident: &Ident) -> TokenStream {
 
     quote! {
         Some(::openspec_jsonrpsee::spec::ContentDescriptor {
-            name: String::from(#name),
+            name: #name,
             summary: None,
             description: None,
             required: Some(true),
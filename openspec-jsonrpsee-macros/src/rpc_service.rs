@@ -0,0 +1,155 @@
+use crate::rpc_method::{RpcMethodArgs, fn_arg_drop_attrs, generate_items, service_output_ident};
+use heck::AsUpperCamelCase;
+use proc_macro::TokenStream;
+use proc_macro_error::abort;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Attribute, FnArg, Ident, ImplItem, ImplItemFn, ItemFn, ItemImpl, Type, parse_quote,
+    visit_mut::{self, VisitMut},
+};
+
+/// Generates the methods marked `#[rpc]` in an `impl` block, with `&self` as their context, and
+/// registers them all with `SpecModule::from_service`.
+pub fn generate_rpc_service(mut input: ItemImpl, args: RpcMethodArgs) -> TokenStream {
+    if !args.is_empty() {
+        abort!(
+            input.impl_token,
+            "Arguments of `#[rpc]` go on the methods of an impl block";
+            help = "mark each method with its own `#[rpc(...)]`"
+        );
+    }
+    if let Some((_, trait_, _)) = &input.trait_ {
+        abort!(trait_, "`#[rpc]` can't be used on a trait implementation");
+    }
+    if !input.generics.params.is_empty() {
+        abort!(
+            input.generics,
+            "`#[rpc]` can't be used on a generic impl block"
+        );
+    }
+
+    let self_ty = (*input.self_ty).clone();
+    let mut items = Vec::new();
+    let mut output_idents = Vec::new();
+    for item in input.items.iter_mut() {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let Some(position) = method.attrs.iter().position(is_rpc_attr) else {
+            continue;
+        };
+        let attr = method.attrs.remove(position);
+        let args = RpcMethodArgs::from_attr(&attr);
+
+        let input = method_as_fn(method, &self_ty);
+        args.validate(&input);
+        items.push(generate_items(&input, Some(&self_ty), &args));
+        fn_arg_drop_attrs(&mut method.sig.inputs);
+        let output_ident = Ident::new(
+            &AsUpperCamelCase(method.sig.ident.to_string()).to_string(),
+            method.sig.ident.span(),
+        );
+        output_idents.push(service_output_ident(&self_ty, &output_ident));
+    }
+
+    let impl_rpc_service = gen_impl_rpc_service(&self_ty, &output_idents);
+
+    quote! {
+        #input
+
+        #(#items)*
+
+        #impl_rpc_service
+    }
+    .into()
+}
+
+fn is_rpc_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "rpc")
+}
+
+/// Turns the method into a function taking the service as its `#[context]` instead of `&self`, and
+/// naming the service instead of `Self`, the function being generated outside the impl block.
+fn method_as_fn(method: &ImplItemFn, self_ty: &Type) -> ItemFn {
+    let mut sig = method.sig.clone();
+    if !sig.generics.params.is_empty() {
+        abort!(
+            sig.generics,
+            "Methods of an impl block can't be generic";
+            help = "use a generic free function, instantiated with e.g. `List::<User>::named(\"users.list\")`"
+        );
+    }
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        Some(FnArg::Receiver(receiver)) => abort!(
+            receiver,
+            "RPC methods must take `&self`";
+            help = "use interior mutability (e.g. a `Mutex`) to change the service"
+        ),
+        _ => abort!(
+            sig.ident,
+            "RPC methods of an impl block must take `&self`";
+            help = "`&self` is the context of the method, use a free function otherwise"
+        ),
+    }
+    if let Some(FnArg::Typed(arg)) = sig.inputs.iter().find(|arg| {
+        matches!(arg, FnArg::Typed(arg) if arg.attrs.iter().any(|attr| attr.path().is_ident("context")))
+    }) {
+        abort!(arg, "`&self` is already the context of the method");
+    }
+
+    let receiver: FnArg = parse_quote! { #[context] __self: &#self_ty };
+    sig.inputs[0] = receiver;
+    ReplaceSelf(self_ty).visit_signature_mut(&mut sig);
+    let attrs = method.attrs.clone();
+
+    ItemFn {
+        attrs,
+        vis: method.vis.clone(),
+        sig,
+        block: Box::new(parse_quote!({})),
+    }
+}
+
+/// Replaces the `Self` types, e.g. in `-> Self` or `Vec<Self>`, with the type of the service
+struct ReplaceSelf<'a>(&'a Type);
+
+impl VisitMut for ReplaceSelf<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(path) = ty
+            && path.qself.is_none()
+            && path.path.is_ident("Self")
+        {
+            *ty = self.0.clone();
+            return;
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+fn gen_impl_rpc_service(self_ty: &Type, output_idents: &[Ident]) -> TokenStream2 {
+    #[cfg(not(feature = "server"))]
+    {
+        let _ = (self_ty, output_idents);
+        quote! {}
+    }
+    #[cfg(feature = "server")]
+    {
+        quote! {
+            impl ::openspec_jsonrpsee::RpcService for #self_ty {
+                #[allow(unused_variables)]
+                fn register_methods(
+                    module: &mut ::openspec_jsonrpsee::SpecModule<Self>,
+                ) -> ::std::result::Result<(), ::jsonrpsee::core::RegisterMethodError> {
+                    #(module.add_method(#output_idents)?;)*
+                    Ok(())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! This module provides typed JSON-RPC batch requests built from the `batch_item` helpers
+//! generated by the [`crate::rpc`] macro, so several calls share one round trip while every
+//! result is still decoded into the response type of its method.
+//!
+//! # Example
+//! ```no_run
+//! use openspec_jsonrpsee::{batch_request, rpc};
+//! use jsonrpsee::http_client::HttpClient;
+//!
+//! #[rpc]
+//! fn get_name(id: u32) -> String {
+//!     format!("user-{id}")
+//! }
+//!
+//! #[rpc]
+//! fn count_users() -> usize {
+//!     42
+//! }
+//!
+//! async fn page(client: &HttpClient) -> Result<(), jsonrpsee::core::ClientError> {
+//!     // A tuple of items decodes into a tuple of results
+//!     let (name, count) =
+//!         batch_request(client, (GetName::batch_item(1), CountUsers::batch_item())).await?;
+//!     println!("{} of {}", name?, count?);
+//!
+//!     // A `Vec` of items (of the same method) decodes into a `Vec` of results
+//!     let names = batch_request(client, (1..=3).map(GetName::batch_item).collect::<Vec<_>>()).await?;
+//!     Ok(())
+//! }
+//! ```
+use jsonrpsee::core::{
+    ClientError,
+    client::{BatchEntry, ClientT},
+    params::{ArrayParams, BatchRequestBuilder},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+/// A single call of a batch request, which knows the type its response decodes into.
+///
+/// # Type Parameters
+/// - `Response`: The response type of the method being called.
+pub struct BatchItem<Response> {
+    method: &'static str,
+    params: ArrayParams,
+    response: PhantomData<fn() -> Response>,
+}
+
+impl<Response: DeserializeOwned> BatchItem<Response> {
+    /// Creates a call to `method` with the given params. Prefer the generated `batch_item`
+    /// helper of a method, which is type safe for both the params and response.
+    pub fn new(method: &'static str, params: ArrayParams) -> Self {
+        BatchItem {
+            method,
+            params,
+            response: PhantomData,
+        }
+    }
+
+    /// Returns the name of the method being called.
+    pub fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// Adds the call to an untyped `jsonrpsee` batch. The matching entry of the batch response
+    /// (requested with [`serde_json::Value`] responses) can be decoded with [`BatchItem::decode`].
+    pub fn insert_into(
+        self,
+        builder: &mut BatchRequestBuilder<'static>,
+    ) -> Result<(), serde_json::Error> {
+        builder.insert(self.method, self.params)
+    }
+
+    /// Decodes the entry of the batch response for this call.
+    pub fn decode(entry: BatchEntry<'_, Value>) -> Result<Response, ClientError> {
+        match entry {
+            Ok(value) => serde_json::from_value(value).map_err(ClientError::ParseError),
+            Err(error) => Err(ClientError::Call(error.into_owned())),
+        }
+    }
+}
+
+/// A set of [`BatchItem`]s that can be sent as one batch request, decoding into [`TypedBatch::Output`].
+///
+/// This is implemented for tuples (up to 12) of items with any response types and for a [`Vec`]
+/// of items with the same response type.
+pub trait TypedBatch {
+    /// The decoded results, one per item of the batch in the same order.
+    type Output;
+
+    /// Converts the items into an untyped `jsonrpsee` batch.
+    fn into_builder(self) -> Result<BatchRequestBuilder<'static>, serde_json::Error>;
+
+    /// Decodes the entries of the batch response, which must be in the order of the items.
+    fn decode(entries: Vec<BatchEntry<'_, Value>>) -> Self::Output;
+}
+
+impl<Response: DeserializeOwned> TypedBatch for Vec<BatchItem<Response>> {
+    type Output = Vec<Result<Response, ClientError>>;
+
+    fn into_builder(self) -> Result<BatchRequestBuilder<'static>, serde_json::Error> {
+        let mut builder = BatchRequestBuilder::new();
+        for item in self {
+            item.insert_into(&mut builder)?;
+        }
+        Ok(builder)
+    }
+
+    fn decode(entries: Vec<BatchEntry<'_, Value>>) -> Self::Output {
+        entries
+            .into_iter()
+            .map(BatchItem::<Response>::decode)
+            .collect()
+    }
+}
+
+macro_rules! impl_typed_batch_for_tuple {
+    ($($response:ident . $index:tt),+) => {
+        impl<$($response: DeserializeOwned),+> TypedBatch for ($(BatchItem<$response>,)+) {
+            type Output = ($(Result<$response, ClientError>,)+);
+
+            fn into_builder(self) -> Result<BatchRequestBuilder<'static>, serde_json::Error> {
+                let mut builder = BatchRequestBuilder::new();
+                $(self.$index.insert_into(&mut builder)?;)+
+                Ok(builder)
+            }
+
+            fn decode(entries: Vec<BatchEntry<'_, Value>>) -> Self::Output {
+                let mut entries = entries.into_iter();
+                ($(
+                    entries
+                        .next()
+                        .map_or_else(|| Err(missing_entry()), BatchItem::<$response>::decode),
+                )+)
+            }
+        }
+    };
+}
+
+impl_typed_batch_for_tuple!(A.0);
+impl_typed_batch_for_tuple!(A.0, B.1);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10);
+impl_typed_batch_for_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10, L.11);
+
+fn missing_entry() -> ClientError {
+    ClientError::Custom("batch response is missing an entry".into())
+}
+
+/// Sends the items as one JSON-RPC batch request and decodes every entry of the response.
+///
+/// # Returns
+/// - `Ok(B::Output)`: The result of every call, which fail individually (e.g. an error response).
+/// - `Err(ClientError)`: An error if the batch as a whole failed (e.g. the transport).
+pub async fn batch_request<B: TypedBatch>(
+    client: &impl ClientT,
+    batch: B,
+) -> Result<B::Output, ClientError> {
+    let builder = batch.into_builder().map_err(ClientError::ParseError)?;
+    let response = client.batch_request::<Value>(builder).await?;
+    Ok(B::decode(response.into_iter().collect()))
+}
@@ -0,0 +1,611 @@
+//! This module loads OpenRPC documents from disk and validates them, e.g. a committed `spec.json`
+//! before serving a [`MockServer`](crate::MockServer) from it.
+//!
+//! Documents are read as JSON or, with the `yaml` feature, as YAML. With the `validate` feature, a
+//! document is checked against the bundled OpenRPC 1.3.2 meta-schema and against the rules the
+//! meta-schema can't express:
+//! - Method names are unique, and so are the param names of each method.
+//! - Required params come before optional ones in methods taking their params by position.
+//! - Local `$ref`s (`#/...`) resolve, against the enclosing schema or the whole document.
+//! - Links target methods of the document.
+//!
+//! Documents generated by a `SpecModule` are also checked for problems of their generation, see
+//! [`SpecModule::validate_spec`](crate::SpecModule::validate_spec).
+//!
+//! # Example
+//! ```no_run
+//! use openspec_jsonrpsee::OpenRpcSpec;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let spec = OpenRpcSpec::from_path("spec.json")?;
+//!     if let Err(error) = spec.validate() {
+//!         for diagnostic in &error.diagnostics {
+//!             eprintln!("{diagnostic}");
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use crate::{OpenRpcSpec, spec};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::Path,
+    str::FromStr,
+};
+
+impl OpenRpcSpec {
+    /// Reads the OpenRPC document at `path`, as YAML if its extension is `.yaml` or `.yml`, as JSON
+    /// if it's `.json`, and as either otherwise (see [`OpenRpcSpec::from_str`]).
+    ///
+    /// # Returns
+    /// - `Ok(OpenRpcSpec)`: The document.
+    /// - `Err(SpecLoadError)`: An error if the file can't be read or isn't a valid document.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, SpecLoadError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(SpecLoadError::Io)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => from_yaml(&contents),
+            Some("json") => from_json(&contents),
+            _ => contents.parse(),
+        }
+    }
+}
+
+/// Parses an OpenRPC document as JSON or, failing that, as YAML. The error is the JSON one if the
+/// document looks like JSON, i.e. starts with `{`, or if the `yaml` feature is disabled.
+impl FromStr for OpenRpcSpec {
+    type Err = SpecLoadError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let json_error = match from_json(contents) {
+            Ok(spec) => return Ok(spec),
+            Err(e) => e,
+        };
+        #[cfg(feature = "yaml")]
+        if !strip_bom(contents).trim_start().starts_with('{') {
+            return from_yaml(contents);
+        }
+        Err(json_error)
+    }
+}
+
+/// Strips the byte order mark some editors write at the start of UTF-8 files.
+fn strip_bom(contents: &str) -> &str {
+    contents.strip_prefix('\u{feff}').unwrap_or(contents)
+}
+
+fn from_json(contents: &str) -> Result<OpenRpcSpec, SpecLoadError> {
+    serde_json::from_str(strip_bom(contents)).map_err(SpecLoadError::Json)
+}
+
+#[cfg(feature = "yaml")]
+fn from_yaml(contents: &str) -> Result<OpenRpcSpec, SpecLoadError> {
+    serde_norway::from_str(contents).map_err(SpecLoadError::Yaml)
+}
+
+#[cfg(not(feature = "yaml"))]
+fn from_yaml(_contents: &str) -> Result<OpenRpcSpec, SpecLoadError> {
+    Err(SpecLoadError::YamlDisabled)
+}
+
+/// An error loading an OpenRPC document with [`OpenRpcSpec::from_path`] or
+/// [`OpenRpcSpec::from_str`].
+///
+/// Non-exhaustive, as [`SpecLoadError::Yaml`] only exists with the `yaml` feature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SpecLoadError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The JSON isn't a valid document.
+    Json(serde_json::Error),
+    /// The YAML isn't a valid document.
+    #[cfg(feature = "yaml")]
+    Yaml(serde_norway::Error),
+    /// The document is YAML, but the `yaml` feature is disabled. Never returned with the feature,
+    /// but declared in every build so matching it doesn't depend on the features of other crates.
+    YamlDisabled,
+}
+
+impl fmt::Display for SpecLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecLoadError::Io(e) => write!(f, "failed to read the document: {e}"),
+            SpecLoadError::Json(e) => write!(f, "invalid JSON document: {e}"),
+            #[cfg(feature = "yaml")]
+            SpecLoadError::Yaml(e) => write!(f, "invalid YAML document: {e}"),
+            SpecLoadError::YamlDisabled => {
+                write!(f, "YAML documents require the `yaml` feature")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecLoadError {}
+
+/// A problem found validating an OpenRPC document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The JSON pointer to the invalid value, e.g. `/methods/0/params/1` (empty for the
+    /// document itself).
+    pub pointer: String,
+    /// The rule that was broken.
+    pub kind: DiagnosticKind,
+    /// What's wrong, readable.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pointer.as_str() {
+            "" => write!(f, "{}", self.message),
+            pointer => write!(f, "{pointer}: {}", self.message),
+        }
+    }
+}
+
+/// The rule broken by a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The document doesn't match the OpenRPC meta-schema.
+    MetaSchema,
+    /// Two methods have the same name.
+    DuplicateMethodName,
+    /// Two params of a method have the same name.
+    DuplicateParamName,
+    /// A required param comes after an optional one in a method taking its params by position.
+    RequiredParamAfterOptional,
+    /// A local `$ref` doesn't resolve.
+    UnresolvedReference,
+    /// A link targets a method that isn't in the document.
+    UnknownLinkMethod,
+    /// A param is of type `()`, so it carries no value.
+    UnitParam,
+    /// Two methods have results of the same name.
+    DuplicateResultName,
+    /// The document doesn't read back as it's written.
+    RoundTrip,
+    /// The info of the API wasn't set.
+    MissingInfo,
+    /// A server URL has a `{variable}` that isn't declared in its variables.
+    UnresolvedServerVariable,
+}
+
+/// The diagnostics of validating an OpenRPC document, see `OpenRpcSpec::validate` and
+/// [`SpecModule::validate_spec`](crate::SpecModule::validate_spec).
+#[derive(Debug, Clone)]
+pub struct SpecValidationError {
+    /// Every problem found.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for SpecValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid OpenRPC document:")?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n- {diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SpecValidationError {}
+
+/// Returns the JSON pointer of the path, escaping `~` and `/` in its tokens.
+fn pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// Checks a document generated by a [`SpecModule`](crate::SpecModule) for the problems of its
+/// generation, validating it too with the `validate` feature.
+pub(crate) fn validate_generated(spec: &OpenRpcSpec) -> Result<(), SpecValidationError> {
+    #[cfg(feature = "validate")]
+    let mut diagnostics = spec
+        .validate()
+        .err()
+        .map(|error| error.diagnostics)
+        .unwrap_or_default();
+    #[cfg(not(feature = "validate"))]
+    let mut diagnostics = Vec::new();
+
+    check_info(spec, &mut diagnostics);
+    check_server_variables(spec, &mut diagnostics);
+    check_generated_methods(spec, &mut diagnostics);
+    check_round_trip(spec, &mut diagnostics);
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(SpecValidationError { diagnostics })
+    }
+}
+
+fn check_info(spec: &OpenRpcSpec, diagnostics: &mut Vec<Diagnostic>) {
+    let info = &spec.info;
+    let message = if info.title.trim().is_empty() || info.version.trim().is_empty() {
+        "the title and version of the API are required"
+    } else if info.title == env!("CARGO_PKG_NAME") && info.version == env!("CARGO_PKG_VERSION") {
+        // The defaults of `Info` are the name and version of this crate, not of the API
+        "the info of the API wasn't set, set it with `SpecModule::set_spec_info`"
+    } else {
+        return;
+    };
+    diagnostics.push(Diagnostic {
+        pointer: "/info".into(),
+        kind: DiagnosticKind::MissingInfo,
+        message: message.into(),
+    });
+}
+
+fn check_server_variables(spec: &OpenRpcSpec, diagnostics: &mut Vec<Diagnostic>) {
+    let servers = spec.servers.iter().flatten().enumerate();
+    let servers = servers.map(|(index, server)| (format!("/servers/{index}"), server));
+    let method_servers = spec.methods.iter().enumerate().flat_map(|(index, method)| {
+        let servers = method.servers.iter().flatten().enumerate();
+        let servers = servers.map(move |(position, server)| {
+            (format!("/methods/{index}/servers/{position}"), server)
+        });
+        let links = method.links.iter().flatten().enumerate();
+        let link_servers = links.filter_map(move |(position, link)| {
+            let server = link.server.as_ref()?;
+            Some((format!("/methods/{index}/links/{position}/server"), server))
+        });
+        servers.chain(link_servers)
+    });
+
+    for (pointer, server) in servers.chain(method_servers) {
+        for variable in url_variables(&server.url) {
+            let declared = server.variables.as_ref();
+            if !declared.is_some_and(|variables| variables.contains_key(variable)) {
+                diagnostics.push(Diagnostic {
+                    pointer: format!("{pointer}/url"),
+                    kind: DiagnosticKind::UnresolvedServerVariable,
+                    message: format!(
+                        "the variable `{{{variable}}}` of the URL `{}` isn't declared in the \
+                         variables of the server",
+                        server.url
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Returns the names of the `{variables}` of a server URL.
+fn url_variables(url: &str) -> Vec<&str> {
+    url.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(variable, _)| variable)
+        .collect()
+}
+
+fn check_generated_methods(spec: &OpenRpcSpec, diagnostics: &mut Vec<Diagnostic>) {
+    let mut results = HashMap::new();
+    for (index, method) in spec.methods.iter().enumerate() {
+        for (position, param) in method.params.iter().enumerate() {
+            if param.schema.as_value().get("type") == Some(&Value::from("null")) {
+                diagnostics.push(Diagnostic {
+                    pointer: format!("/methods/{index}/params/{position}"),
+                    kind: DiagnosticKind::UnitParam,
+                    message: format!(
+                        "the param `{}` of `{}` is of type `()`, so it carries no value",
+                        param.name, method.name
+                    ),
+                });
+            }
+        }
+
+        let Some(result) = &method.result else {
+            continue;
+        };
+        if let Some(other) = results.insert(result.name.as_str(), method.name.as_str()) {
+            diagnostics.push(Diagnostic {
+                pointer: format!("/methods/{index}/result/name"),
+                kind: DiagnosticKind::DuplicateResultName,
+                message: format!(
+                    "the result name `{}` of `{}` is already the result name of `{other}`",
+                    result.name, method.name
+                ),
+            });
+        }
+    }
+}
+
+/// Checks that the methods, then the whole document, read back as they're written, e.g. that no
+/// example has a `null` value, read back as no value.
+fn check_round_trip(spec: &OpenRpcSpec, diagnostics: &mut Vec<Diagnostic>) {
+    let count = diagnostics.len();
+    for (index, method) in spec.methods.iter().enumerate() {
+        let path = vec!["methods".to_string(), index.to_string()];
+        let what = format!("the method `{}`", method.name);
+        if let Some(diagnostic) = round_trip_diagnostic::<spec::Method>(method, &what, path) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    // The methods being in the document, it's only checked once they all read back
+    if diagnostics.len() == count
+        && let Some(diagnostic) =
+            round_trip_diagnostic::<OpenRpcSpec>(spec, "the document", Vec::new())
+    {
+        diagnostics.push(diagnostic);
+    }
+}
+
+fn round_trip_diagnostic<T>(value: &T, what: &str, mut path: Vec<String>) -> Option<Diagnostic>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let written = serde_json::to_value(value).expect("spec should serialize");
+    let message = match serde_json::from_value::<T>(written.clone()) {
+        Ok(read) => {
+            let read = serde_json::to_value(read).expect("spec should serialize");
+            if !first_difference(&written, &read, &mut path) {
+                return None;
+            }
+            format!("{what} doesn't read back as it's written")
+        }
+        Err(e) => format!("{what} can't be read back: {e}"),
+    };
+    Some(Diagnostic {
+        pointer: pointer(&path),
+        kind: DiagnosticKind::RoundTrip,
+        message,
+    })
+}
+
+/// Returns true if the values differ, extending the path to the first difference.
+fn first_difference(written: &Value, read: &Value, path: &mut Vec<String>) -> bool {
+    match (written, read) {
+        (Value::Object(written), Value::Object(read)) => {
+            let keys: BTreeSet<&String> = written.keys().chain(read.keys()).collect();
+            keys.into_iter().any(|key| {
+                path.push(key.clone());
+                let differ = match (written.get(key), read.get(key)) {
+                    (Some(written), Some(read)) => first_difference(written, read, path),
+                    _ => true,
+                };
+                if !differ {
+                    path.pop();
+                }
+                differ
+            })
+        }
+        (Value::Array(written), Value::Array(read)) if written.len() == read.len() => written
+            .iter()
+            .zip(read)
+            .enumerate()
+            .any(|(index, (written, read))| {
+                path.push(index.to_string());
+                let differ = first_difference(written, read, path);
+                if !differ {
+                    path.pop();
+                }
+                differ
+            }),
+        (written, read) => written != read,
+    }
+}
+
+#[cfg(feature = "validate")]
+pub use validation::*;
+
+#[cfg(feature = "validate")]
+mod validation {
+    use super::{Diagnostic, DiagnosticKind, SpecValidationError, pointer};
+    use crate::OpenRpcSpec;
+    use serde_json::{Map, Value};
+    use std::{collections::HashSet, sync::LazyLock};
+
+    /// The OpenRPC 1.3.2 meta-schema, validating the structure of documents. The schemas of the
+    /// document are validated against the JSON Schema draft 7 meta-schema.
+    pub const OPENRPC_META_SCHEMA: &str = include_str!("openrpc-meta-schema.json");
+
+    static META_SCHEMA: LazyLock<jsonschema::Validator> = LazyLock::new(|| {
+        let schema = serde_json::from_str(OPENRPC_META_SCHEMA)
+            .expect("the meta-schema should be valid JSON");
+        jsonschema::draft7::new(&schema).expect("the meta-schema should compile")
+    });
+
+    impl OpenRpcSpec {
+        /// Validates the document against the OpenRPC meta-schema and the rules it can't express,
+        /// see [`validate_document`].
+        pub fn validate(&self) -> Result<(), SpecValidationError> {
+            validate_document(&serde_json::to_value(self).expect("spec should serialize"))
+        }
+    }
+
+    /// Validates a raw OpenRPC document against the OpenRPC meta-schema and the rules it can't
+    /// express (unique names, param order, `$ref`s and link targets), collecting every problem.
+    ///
+    /// # Returns
+    /// - `Ok(())`: The document is valid.
+    /// - `Err(SpecValidationError)`: The diagnostics of every problem found.
+    pub fn validate_document(document: &Value) -> Result<(), SpecValidationError> {
+        let mut diagnostics: Vec<Diagnostic> = META_SCHEMA
+            .iter_errors(document)
+            .map(|error| Diagnostic {
+                pointer: error.instance_path().as_str().to_string(),
+                kind: DiagnosticKind::MetaSchema,
+                message: error.to_string(),
+            })
+            .collect();
+
+        check_methods(document, &mut diagnostics);
+        check_refs(document, document, &mut Vec::new(), None, &mut diagnostics);
+        check_links(document, &mut diagnostics);
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(SpecValidationError { diagnostics })
+        }
+    }
+
+    fn methods(document: &Value) -> impl Iterator<Item = (usize, &Map<String, Value>)> {
+        document["methods"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, method)| Some((index, method.as_object()?)))
+    }
+
+    fn check_methods(document: &Value, diagnostics: &mut Vec<Diagnostic>) {
+        let mut names = HashSet::new();
+        for (index, method) in methods(document) {
+            let Some(name) = method.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            if !names.insert(name) {
+                diagnostics.push(Diagnostic {
+                    pointer: format!("/methods/{index}/name"),
+                    kind: DiagnosticKind::DuplicateMethodName,
+                    message: format!("the method name `{name}` is not unique"),
+                });
+            }
+            check_params(index, method, diagnostics);
+        }
+    }
+
+    fn check_params(index: usize, method: &Map<String, Value>, diagnostics: &mut Vec<Diagnostic>) {
+        let by_position =
+            method.get("paramStructure").and_then(Value::as_str) == Some("by-position");
+        let mut names = HashSet::new();
+        let mut optional = None;
+        // Referenced params are skipped, their name and requirement being held elsewhere
+        let params = method
+            .get("params")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for (position, param) in params.enumerate() {
+            let Some(name) = param.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let pointer = format!("/methods/{index}/params/{position}");
+            if !names.insert(name) {
+                diagnostics.push(Diagnostic {
+                    pointer: pointer.clone(),
+                    kind: DiagnosticKind::DuplicateParamName,
+                    message: format!("the param name `{name}` is not unique"),
+                });
+            }
+            let required = param
+                .get("required")
+                .and_then(Value::as_bool)
+                .unwrap_or_default();
+            match optional {
+                Some(optional) if by_position && required => diagnostics.push(Diagnostic {
+                    pointer,
+                    kind: DiagnosticKind::RequiredParamAfterOptional,
+                    message: format!(
+                        "the required param `{name}` comes after the optional param `{optional}`, \
+                         but params are by position"
+                    ),
+                }),
+                None if !required => optional = Some(name),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns true if the value at the path is the root of a JSON schema, its local `$ref`s
+    /// resolving against it.
+    fn is_schema_root(path: &[String]) -> bool {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        matches!(
+            path.as_slice(),
+            ["methods", _, "params", _, "schema"]
+                | ["methods", _, "result", "schema"]
+                | ["components", "contentDescriptors", _, "schema"]
+                | ["components", "schemas", _]
+        )
+    }
+
+    fn check_refs<'a>(
+        document: &'a Value,
+        value: &'a Value,
+        path: &mut Vec<String>,
+        schema: Option<&'a Value>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let schema = match schema {
+            None if is_schema_root(path) => Some(value),
+            schema => schema,
+        };
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref")
+                    && let Some(target) = reference.strip_prefix('#')
+                    && ![schema, Some(document)]
+                        .into_iter()
+                        .flatten()
+                        .any(|root| root.pointer(target).is_some())
+                {
+                    diagnostics.push(Diagnostic {
+                        pointer: pointer(path),
+                        kind: DiagnosticKind::UnresolvedReference,
+                        message: format!("the reference `{reference}` doesn't resolve"),
+                    });
+                }
+                for (key, child) in object {
+                    path.push(key.clone());
+                    check_refs(document, child, path, schema, diagnostics);
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                for (index, child) in items.iter().enumerate() {
+                    path.push(index.to_string());
+                    check_refs(document, child, path, schema, diagnostics);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_links(document: &Value, diagnostics: &mut Vec<Diagnostic>) {
+        let names: HashSet<&str> = methods(document)
+            .filter_map(|(_, method)| method.get("name")?.as_str())
+            .collect();
+        let method_links = methods(document).flat_map(|(index, method)| {
+            let links = method
+                .get("links")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten();
+            links
+                .enumerate()
+                .map(move |(position, link)| (format!("/methods/{index}/links/{position}"), link))
+        });
+        let component_links = document["components"]["links"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, link)| {
+                (
+                    pointer(&["components".into(), "links".into(), key.clone()]),
+                    link,
+                )
+            });
+
+        for (pointer, link) in method_links.chain(component_links) {
+            if let Some(method) = link.get("method").and_then(Value::as_str)
+                && !names.contains(method)
+            {
+                diagnostics.push(Diagnostic {
+                    pointer: format!("{pointer}/method"),
+                    kind: DiagnosticKind::UnknownLinkMethod,
+                    message: format!("the link targets the unknown method `{method}`"),
+                });
+            }
+        }
+    }
+}
@@ -1,8 +1,22 @@
+#[cfg(feature = "client")]
+pub mod batch;
+pub mod document;
+#[doc(hidden)]
+pub mod macro_support;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod rpc;
+#[cfg(any(feature = "mock", feature = "test"))]
+mod schema;
 pub mod spec;
 #[cfg(feature = "test")]
 pub mod test;
 
+#[cfg(feature = "client")]
+pub use crate::batch::*;
+pub use crate::document::*;
+#[cfg(feature = "mock")]
+pub use crate::mock::*;
 pub use crate::rpc::*;
 pub use crate::spec::*;
 #[cfg(feature = "test")]
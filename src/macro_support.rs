@@ -0,0 +1,83 @@
+//! This module defines the checks `#[rpc]` generates on the types of a method, spanned on the
+//! types, so a type missing a trait is reported on the signature instead of inside the generated
+//! code.
+//!
+//! Not part of the public API.
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// The stream returned by the `paginate` function of paginated methods
+pub use futures_util::Stream;
+
+/// The result name of an instance of a generic method, from the name it's instantiated under,
+/// e.g. `GetUserResponse` for `get_user` or `UsersListResponse` for `users.list`
+pub fn instance_result_name(name: &str) -> String {
+    let mut result_name: String = name
+        .split(|c: char| !c.is_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect();
+    result_name.push_str("Response");
+    result_name
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+    use schemars::JsonSchema;
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` can't be used as the type of an RPC param",
+        label = "params must implement `Deserialize` and `JsonSchema`",
+        note = "derive them with `#[derive(serde::Deserialize, schemars::JsonSchema)]`"
+    )]
+    pub trait ServerParam<'de>: Deserialize<'de> + JsonSchema {}
+    impl<'de, T: Deserialize<'de> + JsonSchema> ServerParam<'de> for T {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` can't be returned by an RPC method",
+        label = "results must implement `Serialize`, `Clone` and `JsonSchema`",
+        note = "derive them with `#[derive(Clone, serde::Serialize, schemars::JsonSchema)]`"
+    )]
+    pub trait ServerResult: Serialize + Clone + JsonSchema {}
+    impl<T: Serialize + Clone + JsonSchema> ServerResult for T {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` can't be taken by value as the `#[context]`",
+        label = "the context is shared by the calls, so it's cloned for each of them",
+        note = "take the context by reference, e.g. `#[context] ctx: &{Self}`, or implement `Clone`"
+    )]
+    pub trait ServerOwnedContext: Clone {}
+    impl<T: Clone> ServerOwnedContext for T {}
+
+    pub fn assert_server_param<'de, T: ServerParam<'de>>() {}
+    pub fn assert_server_result<T: ServerResult>() {}
+    pub fn assert_server_owned_context<T: ServerOwnedContext>() {}
+}
+
+#[cfg(feature = "server")]
+pub use server::*;
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be sent as an RPC param",
+    label = "params must implement `Serialize` to be sent by the client",
+    note = "derive it with `#[derive(serde::Serialize)]`"
+)]
+pub trait ClientParam: Serialize {}
+impl<T: Serialize> ClientParam for T {}
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be received as the result of an RPC method",
+    label = "results must implement `DeserializeOwned` to be received by the client",
+    note = "derive it with `#[derive(serde::Deserialize)]`"
+)]
+pub trait ClientResult: DeserializeOwned {}
+impl<T: DeserializeOwned> ClientResult for T {}
+
+pub fn assert_client_param<T: ClientParam>() {}
+pub fn assert_client_result<T: ClientResult>() {}
@@ -0,0 +1,190 @@
+//! This module provides a mock JSON-RPC server built from an OpenRPC document alone, so clients
+//! can be developed and tested against a `spec.json` before (or without) the real `SpecModule`.
+//!
+//! Every method of the document is registered. Its params are validated against the param
+//! schemas, and its response is taken from the method's examples or synthesized from its result schema.
+//!
+//! # Example
+//! ```no_run
+//! use openspec_jsonrpsee::{MockServer, OpenRpcSpec};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let spec: OpenRpcSpec = serde_json::from_str(&std::fs::read_to_string("spec.json")?)?;
+//!     let (handle, addr) = MockServer::from_spec(&spec)?
+//!         .start("127.0.0.1:8080".parse()?)
+//!         .await?;
+//!     println!("Mock server running at: {addr}");
+//!     handle.stopped().await;
+//!     Ok(())
+//! }
+//! ```
+use crate::{
+    NotificationLayer, OpenRpcSpec,
+    schema::{self, ParamsValidator},
+    spec,
+};
+use jsonrpsee::{
+    RpcModule,
+    core::{RegisterMethodError, RpcResult},
+    server::{Server, ServerHandle, middleware::rpc::RpcServiceBuilder},
+    types::{
+        ErrorObject, Params,
+        error::{INVALID_PARAMS_CODE, INVALID_PARAMS_MSG},
+    },
+};
+use serde_json::Value;
+use std::{fmt, net::SocketAddr, sync::Arc};
+
+/// A mock of the API described by an OpenRPC document.
+pub struct MockServer {
+    module: RpcModule<()>,
+}
+
+impl MockServer {
+    /// Creates a mock registering every method of the OpenRPC document.
+    ///
+    /// Method names are leaked to satisfy `jsonrpsee`, which is fine for a mock created once.
+    ///
+    /// # Returns
+    /// - `Ok(MockServer)`: The mock with every method registered.
+    /// - `Err(MockError)`: An error if a schema is invalid or a method name is duplicated.
+    pub fn from_spec(spec: &OpenRpcSpec) -> Result<Self, MockError> {
+        let mut module = RpcModule::new(());
+
+        for method in &spec.methods {
+            let mock = Arc::new(MockMethod::new(method)?);
+            let name: &'static str = Box::leak(method.name.clone().into_boxed_str());
+            module
+                .register_method(name, move |params, _, _| mock.respond(params))
+                .map_err(MockError::Register)?;
+        }
+
+        Ok(MockServer { module })
+    }
+
+    /// Consumes the mock and converts it into a `jsonrpsee::RpcModule`.
+    pub fn into_jsonrpsee_module(self) -> RpcModule<()> {
+        self.module
+    }
+
+    /// Starts a JSON-RPC server for the mock at the given address (use port `0` for any port).
+    ///
+    /// # Returns
+    /// - `Ok((ServerHandle, SocketAddr))`: The handle of the running server and its address.
+    /// - `Err(std::io::Error)`: An error if the server failed to start.
+    pub async fn start(self, addr: SocketAddr) -> std::io::Result<(ServerHandle, SocketAddr)> {
+        let server = Server::builder()
+            .set_rpc_middleware(RpcServiceBuilder::new().layer(NotificationLayer))
+            .build(addr)
+            .await?;
+        let addr = server.local_addr()?;
+        Ok((server.start(self.module), addr))
+    }
+}
+
+/// An error creating a [`MockServer`] from an OpenRPC document.
+#[derive(Debug)]
+pub enum MockError {
+    /// The schema of a param or result isn't a valid JSON schema.
+    InvalidSchema {
+        method: String,
+        content: String,
+        message: String,
+    },
+    /// The method couldn't be registered, e.g. because its name is duplicated.
+    Register(RegisterMethodError),
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockError::InvalidSchema {
+                method,
+                content,
+                message,
+            } => write!(f, "invalid schema for `{content}` of `{method}`: {message}"),
+            MockError::Register(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for MockError {}
+
+/// The mock of a single method.
+struct MockMethod {
+    params: ParamsValidator,
+    examples: Vec<MockExample>,
+    /// Synthesized from the result schema, used when no example matches
+    result: Value,
+}
+
+/// An example pairing, with the values of the params in the order of the method's params.
+struct MockExample {
+    params: Vec<Option<Value>>,
+    result: Value,
+}
+
+impl MockMethod {
+    fn new(method: &spec::Method) -> Result<Self, MockError> {
+        let params =
+            ParamsValidator::new(method).map_err(|(param, message)| MockError::InvalidSchema {
+                method: method.name.clone(),
+                content: param,
+                message,
+            })?;
+
+        let examples = method
+            .examples
+            .iter()
+            .flatten()
+            .filter_map(|pairing| {
+                let result = pairing.result.as_ref()?.value.clone()?;
+                let params = params
+                    .names()
+                    .map(|name| {
+                        let example = pairing
+                            .params
+                            .iter()
+                            .find(|p| p.name.as_deref() == Some(name))?;
+                        example.value.clone()
+                    })
+                    .collect();
+                Some(MockExample { params, result })
+            })
+            .collect();
+
+        let result = method
+            .result
+            .as_ref()
+            .map(|result| schema::sample(result.schema.as_value()))
+            .unwrap_or_default();
+
+        Ok(MockMethod {
+            params,
+            examples,
+            result,
+        })
+    }
+
+    fn respond(&self, params: Params) -> RpcResult<Value> {
+        let values = self
+            .params
+            .validate(params.parse::<Value>()?)
+            .map_err(invalid_params)?;
+
+        let example = self
+            .examples
+            .iter()
+            .find(|example| example.params == values)
+            .or(self.examples.first());
+        match example {
+            Some(example) => Ok(example.result.clone()),
+            None => Ok(self.result.clone()),
+        }
+    }
+}
+
+fn invalid_params(errors: Vec<String>) -> ErrorObject<'static> {
+    ErrorObject::owned(INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, Some(errors))
+}
@@ -0,0 +1,78 @@
+//! This module defines the authorization of calls to methods requiring scopes
+//! (`#[rpc(auth = "admin")]` or `#[rpc(requires = [...])]`), which the [`Authorizer`] set with
+//! [`crate::SpecModule::set_authorizer`] grants or denies from the credentials in the extensions.
+use crate::spec;
+use jsonrpsee::{Extensions, types::ErrorObjectOwned};
+use serde_json::json;
+use std::sync::{Arc, RwLock};
+
+/// The error code responded to calls that aren't authorized, one jsonrpsee doesn't use for its
+/// own errors.
+pub const UNAUTHORIZED_CODE: i32 = -32003;
+/// The error message responded to calls that aren't authorized.
+pub const UNAUTHORIZED_MSG: &str = "Unauthorized";
+
+/// Describes a call to a method requiring scopes, as passed to the [`Authorizer`].
+pub struct AuthorizationRequest<'a> {
+    /// The specification of the method called.
+    pub method: &'a spec::Method,
+    /// The scopes the caller must all be granted, as recorded in `x-security`.
+    pub scopes: &'a [String],
+    /// The extensions of the call, holding the credentials of the caller (e.g. inserted by an
+    /// HTTP or RPC middleware).
+    pub extensions: &'a Extensions,
+}
+
+/// Decides whether a call to a method requiring scopes is authorized.
+///
+/// It's implemented by any `Fn(&AuthorizationRequest) -> bool`.
+pub trait Authorizer: Send + Sync + 'static {
+    /// Returns true if the caller is granted all the scopes of the request.
+    fn authorize(&self, request: &AuthorizationRequest) -> bool;
+}
+
+impl<F> Authorizer for F
+where
+    F: Fn(&AuthorizationRequest) -> bool + Send + Sync + 'static,
+{
+    fn authorize(&self, request: &AuthorizationRequest) -> bool {
+        self(request)
+    }
+}
+
+/// Shared slot for the authorizer, so it applies to methods registered both before and after it's set.
+pub(crate) type AuthorizerSlot = Arc<RwLock<Option<Arc<dyn Authorizer>>>>;
+
+/// Authorizes the calls to one method requiring scopes with the module's authorizer.
+#[derive(Clone)]
+pub(crate) struct AuthorizationGuard {
+    pub(crate) method: Arc<spec::Method>,
+    pub(crate) scopes: Arc<[String]>,
+    pub(crate) authorizer: AuthorizerSlot,
+}
+
+impl AuthorizationGuard {
+    /// Returns the error to respond with if the call isn't authorized.
+    ///
+    /// Calls are denied while no authorizer is set.
+    pub(crate) fn authorize(&self, extensions: &Extensions) -> Result<(), ErrorObjectOwned> {
+        let authorizer = self.authorizer.read().unwrap_or_else(|e| e.into_inner());
+        let authorized = authorizer.as_ref().is_some_and(|authorizer| {
+            authorizer.authorize(&AuthorizationRequest {
+                method: &self.method,
+                scopes: &self.scopes,
+                extensions,
+            })
+        });
+
+        if authorized {
+            Ok(())
+        } else {
+            Err(ErrorObjectOwned::owned(
+                UNAUTHORIZED_CODE,
+                UNAUTHORIZED_MSG,
+                Some(json!({ "required": &*self.scopes })),
+            ))
+        }
+    }
+}
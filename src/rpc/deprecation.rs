@@ -0,0 +1,39 @@
+//! This module defines the hook invoked whenever a deprecated method is called, so servers can
+//! log a warning or record a metric about who still calls methods that are being retired.
+use crate::spec;
+use jsonrpsee::Extensions;
+use std::sync::{Arc, RwLock};
+
+/// Describes a call to a deprecated method, as passed to the hook set with
+/// [`crate::SpecModule::on_deprecated_call`].
+pub struct DeprecatedCall<'a> {
+    /// The specification of the deprecated method, e.g. for its `x-replaced-by` method.
+    pub method: &'a spec::Method,
+    /// The extensions of the call, e.g. to identify the caller by its connection.
+    pub extensions: &'a Extensions,
+}
+
+/// A hook invoked on every call to a deprecated method.
+pub type DeprecatedCallHook = Arc<dyn Fn(&DeprecatedCall) + Send + Sync>;
+
+/// Shared slot for the hook, so it applies to methods registered both before and after it's set.
+pub(crate) type DeprecatedCallHookSlot = Arc<RwLock<Option<DeprecatedCallHook>>>;
+
+/// Notifies the module's hook (if any) about calls to one deprecated method.
+#[derive(Clone)]
+pub(crate) struct DeprecationNotifier {
+    pub(crate) method: Arc<spec::Method>,
+    pub(crate) hook: DeprecatedCallHookSlot,
+}
+
+impl DeprecationNotifier {
+    pub(crate) fn notify(&self, extensions: &Extensions) {
+        let hook = self.hook.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(hook) = hook.as_ref() {
+            hook(&DeprecatedCall {
+                method: &self.method,
+                extensions,
+            });
+        }
+    }
+}
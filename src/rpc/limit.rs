@@ -0,0 +1,295 @@
+//! This module defines the rate and concurrency limits of methods, set with
+//! `#[rpc(rate_limit = "100/s", max_concurrency = 8)]` or for the whole module with
+//! [`crate::SpecModule::set_default_limits`], e.g. to protect a database pool shared by handlers.
+//!
+//! The limits are recorded in the spec (`x-rate-limit` and `x-max-concurrency`) and enforced on
+//! every call before its handler runs, a subscription counting as running until it's closed.
+//! Excess calls are responded to with the [`LIMIT_EXCEEDED_CODE`] error, whose data holds the
+//! `retryAfterMs` of rate limited calls.
+use crate::spec;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde_json::json;
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use typed_builder::TypedBuilder;
+
+/// The error code responded to calls exceeding the limits of their method, jsonrpsee's
+/// `SERVER_IS_BUSY_CODE` (-32009): the call can be retried later, the data telling which limit
+/// was exceeded.
+pub const LIMIT_EXCEEDED_CODE: i32 = jsonrpsee::types::error::SERVER_IS_BUSY_CODE;
+/// The error message responded to calls exceeding the limits of their method.
+pub const LIMIT_EXCEEDED_MSG: &str = "Limit exceeded";
+
+/// A number of calls allowed per period, e.g. `100/s`, `30/m` or `1000/h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of calls allowed per period.
+    pub calls: u32,
+    /// The period: a second, a minute or an hour.
+    pub period: Duration,
+}
+
+impl RateLimit {
+    /// Allows `calls` per second.
+    pub fn per_second(calls: u32) -> Self {
+        RateLimit {
+            calls,
+            period: Duration::from_secs(1),
+        }
+    }
+
+    /// Allows `calls` per minute.
+    pub fn per_minute(calls: u32) -> Self {
+        RateLimit {
+            calls,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    /// Allows `calls` per hour.
+    pub fn per_hour(calls: u32) -> Self {
+        RateLimit {
+            calls,
+            period: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = ParseRateLimitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRateLimitError(s.to_string());
+        let (calls, unit) = s.split_once('/').ok_or_else(error)?;
+        let calls = calls
+            .trim()
+            .parse()
+            .ok()
+            .filter(|calls| *calls > 0)
+            .ok_or_else(error)?;
+        match unit.trim() {
+            "s" => Ok(RateLimit::per_second(calls)),
+            "m" => Ok(RateLimit::per_minute(calls)),
+            "h" => Ok(RateLimit::per_hour(calls)),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.period.as_secs() {
+            1 => write!(f, "{}/s", self.calls),
+            60 => write!(f, "{}/m", self.calls),
+            3600 => write!(f, "{}/h", self.calls),
+            _ => write!(f, "{}/{}ms", self.calls, self.period.as_millis()),
+        }
+    }
+}
+
+/// The error of parsing a [`RateLimit`] that isn't formatted like `100/s`, `30/m` or `1000/h`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRateLimitError(String);
+
+impl fmt::Display for ParseRateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid rate limit `{}`, expected e.g. `100/s`, `30/m` or `1000/h`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseRateLimitError {}
+
+/// The limits of a method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option)))]
+pub struct Limits {
+    /// The number of calls allowed per period, across all connections.
+    pub rate_limit: Option<RateLimit>,
+    /// The number of calls handled at the same time, across all connections.
+    pub max_concurrency: Option<u32>,
+}
+
+impl Limits {
+    /// Returns the limits recorded in the specification of the method.
+    pub fn of(method: &spec::Method) -> Self {
+        Limits {
+            rate_limit: method
+                .extensions
+                .get(spec::extension::RATE_LIMIT)
+                .and_then(|value| value.as_str())
+                .and_then(|value| value.parse().ok()),
+            max_concurrency: method
+                .extensions
+                .get(spec::extension::MAX_CONCURRENCY)
+                .and_then(|value| value.as_u64())
+                .and_then(|value| value.try_into().ok()),
+        }
+    }
+
+    /// Returns true if there's no limit.
+    pub fn is_empty(&self) -> bool {
+        self.rate_limit.is_none() && self.max_concurrency.is_none()
+    }
+
+    /// Returns these limits, falling back to the `defaults` for the limits that aren't set.
+    pub(crate) fn or(self, defaults: Limits) -> Self {
+        Limits {
+            rate_limit: self.rate_limit.or(defaults.rate_limit),
+            max_concurrency: self.max_concurrency.or(defaults.max_concurrency),
+        }
+    }
+
+    /// Records these limits in the specification of the method, replacing any limit recorded.
+    pub(crate) fn record(&self, method: &mut spec::Method) {
+        let extensions = &mut method.extensions;
+        match self.rate_limit {
+            Some(rate_limit) => {
+                extensions.insert(
+                    spec::extension::RATE_LIMIT.to_string(),
+                    json!(rate_limit.to_string()),
+                );
+            }
+            None => {
+                extensions.remove(spec::extension::RATE_LIMIT);
+            }
+        }
+        match self.max_concurrency {
+            Some(max_concurrency) => {
+                extensions.insert(
+                    spec::extension::MAX_CONCURRENCY.to_string(),
+                    json!(max_concurrency),
+                );
+            }
+            None => {
+                extensions.remove(spec::extension::MAX_CONCURRENCY);
+            }
+        }
+    }
+}
+
+/// Enforces the limits of the calls to one method, whose state is shared by all connections.
+#[derive(Clone, Default)]
+pub(crate) struct LimitGuard(Arc<RwLock<Option<Arc<Limiter>>>>);
+
+impl LimitGuard {
+    /// Sets the limits enforced, counting the calls anew.
+    pub(crate) fn set(&self, limits: Limits) {
+        let limiter = (!limits.is_empty()).then(|| {
+            Arc::new(Limiter {
+                bucket: limits.rate_limit.map(|rate_limit| {
+                    Mutex::new(TokenBucket {
+                        rate_limit,
+                        tokens: rate_limit.calls as f64,
+                        refilled_at: Instant::now(),
+                    })
+                }),
+                concurrency: limits.max_concurrency.map(|max| (AtomicU32::new(0), max)),
+            })
+        });
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = limiter;
+    }
+
+    /// Admits a call, returning the permit to hold while it's handled or the error to respond with.
+    pub(crate) fn admit(&self) -> Result<Permit, ErrorObjectOwned> {
+        match &*self.0.read().unwrap_or_else(|e| e.into_inner()) {
+            Some(limiter) => limiter.admit(),
+            None => Ok(Permit(None)),
+        }
+    }
+}
+
+pub(crate) struct Limiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    /// The number of calls being handled, and the maximum
+    concurrency: Option<(AtomicU32, u32)>,
+}
+
+impl Limiter {
+    /// Admits a call, returning the permit to hold while it's handled or the error to respond with.
+    fn admit(self: &Arc<Self>) -> Result<Permit, ErrorObjectOwned> {
+        // The concurrency is checked first, so calls rejected for it don't consume the rate
+        let permit = match &self.concurrency {
+            Some((running, max)) => {
+                running
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                        (running < *max).then_some(running + 1)
+                    })
+                    .map_err(|_| {
+                        limit_exceeded(json!({ "limit": "maxConcurrency", "maxConcurrency": max }))
+                    })?;
+                Permit(Some(self.clone()))
+            }
+            None => Permit(None),
+        };
+
+        if let Some(bucket) = &self.bucket {
+            let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(retry_after) = bucket.take() {
+                return Err(limit_exceeded(json!({
+                    "limit": "rateLimit",
+                    "rateLimit": bucket.rate_limit.to_string(),
+                    "retryAfterMs": retry_after.as_millis().max(1) as u64,
+                })));
+            }
+        }
+
+        Ok(permit)
+    }
+}
+
+/// Allows calls at a steady rate, with bursts of up to the number of calls per period.
+struct TokenBucket {
+    rate_limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Takes a token for a call, or returns how long until the next one is available.
+    fn take(&mut self) -> Result<(), Duration> {
+        let capacity = self.rate_limit.calls as f64;
+        let per_second = capacity / self.rate_limit.period.as_secs_f64();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        }
+    }
+}
+
+/// Counts a call as running until dropped.
+pub(crate) struct Permit(Option<Arc<Limiter>>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some((running, _)) = self
+            .0
+            .as_ref()
+            .and_then(|limiter| limiter.concurrency.as_ref())
+        {
+            running.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+fn limit_exceeded(data: serde_json::Value) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(LIMIT_EXCEEDED_CODE, LIMIT_EXCEEDED_MSG, Some(data))
+}
@@ -1,7 +1,7 @@
 //! This module defines the core abstractions for RPC methods and their handlers in the `openspec_jsonrpsee` framework.
 //!
 //! The module provides:
-//! - Type definitions for synchronous and asynchronous RPC callbacks.
+//! - Type definitions for synchronous, blocking, asynchronous and streaming RPC callbacks.
 //! - The `ServerHandler` enum to represent server-side handlers for RPC methods.
 //! - The `RpcMethod` trait, which serves as the foundation for defining and registering RPC methods.
 //!
@@ -31,12 +31,15 @@
 //!     }
 //! }
 //! ```
-use crate::spec;
+use crate::{ResponseStream, RpcLayer, spec};
 use jsonrpsee::{Extensions, core::RpcResult, types::Params};
 use serde::Serialize;
 
 /// A synchronous callback for an RPC method.
 pub type SyncCallback<Context, Response> = fn(Params, &Context, &Extensions) -> Response;
+/// A synchronous callback for an RPC method, run on the blocking thread pool.
+pub type BlockingCallback<Context, Response> =
+    fn(Params, std::sync::Arc<Context>, Extensions) -> Response;
 /// An asynchronous callback for an RPC method.
 pub type AsyncCallback<Context, Response> =
     fn(
@@ -44,15 +47,29 @@ pub type AsyncCallback<Context, Response> =
         ::std::sync::Arc<Context>,
         ::jsonrpsee::Extensions,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>;
+/// An asynchronous callback for an RPC method streaming its result, whose items are responses.
+pub type StreamCallback<Context, Response> = fn(
+    ::jsonrpsee::types::Params<'static>,
+    ::std::sync::Arc<Context>,
+    ::jsonrpsee::Extensions,
+) -> std::pin::Pin<
+    Box<dyn std::future::Future<Output = RpcResult<ResponseStream<Response>>> + Send>,
+>;
 
-/// This enum represents the server-side handler for an RPC method, which can be either synchronous or asynchronous.
+/// This enum represents the server-side handler for an RPC method, which can be either synchronous
+/// (run inline, or on the blocking thread pool for CPU-heavy methods), asynchronous, or streaming
+/// its result to a subscriber.
 ///
 /// # Type Parameters
 /// - `Context`: The type of the context can be passed to the handler.
 /// - `Response`: The type of the response returned by the handler. Must implement [`Serialize`], [`Clone`], and `'static`.
 pub enum ServerHandler<Context, Response> {
     Sync(SyncCallback<Context, Response>),
+    /// Registered with `register_blocking_method`, set with `#[rpc(blocking)]`
+    Blocking(BlockingCallback<Context, Response>),
     Async(AsyncCallback<Context, Response>),
+    /// Registered as a subscription, for methods returning an [`crate::RpcStream`]
+    Stream(StreamCallback<Context, Response>),
 }
 
 /// Represents an RPC method that can be registered with a [`openspec_jsonrpsee::SpecModule`]].
@@ -77,6 +94,13 @@ pub trait RpcMethod<Context, Response: Serialize + Clone + 'static = ()> {
     /// Returns a function (static) that handles the RPC request for the server.
     ///
     /// The handler is responsible for processing incoming requests and producing a response.
-    /// It can be either synchronous or asynchronous, as represented by [`ServerHandler`].
+    /// It can be either synchronous, blocking or asynchronous, as represented by [`ServerHandler`].
     fn handler(&self) -> ServerHandler<Context, RpcResult<Response>>;
+
+    /// Returns the layers scoped to the method, set with `#[rpc(middleware = ...)]`.
+    ///
+    /// They only take effect with the layer of [`crate::SpecModule::rpc_middleware`] set on the server.
+    fn middleware(&self) -> Vec<RpcLayer> {
+        Vec::new()
+    }
 }
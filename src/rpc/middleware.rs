@@ -0,0 +1,331 @@
+//! This module defines RPC middleware scoped to the methods of a [`crate::SpecModule`], e.g. to
+//! authenticate only the methods tagged `admin`, instead of branching on method names in a
+//! middleware set for the whole server.
+//!
+//! Layers are attached with [`crate::SpecModule::layer_for_method`],
+//! [`crate::SpecModule::layer_for_tag`] or `#[rpc(middleware = ...)]`, and take effect once the
+//! layer returned by [`crate::SpecModule::rpc_middleware`] is set on the server. Every request to a
+//! method of the module carries its [`MethodMetadata`] in its extensions.
+//!
+//! # Example
+//! ```no_run
+//! use openspec_jsonrpsee::{MethodMetadata, SpecModule};
+//! use jsonrpsee::server::{
+//!     Server,
+//!     middleware::rpc::{RpcServiceBuilder, RpcServiceT, Request, MethodResponse, Batch, Notification},
+//! };
+//!
+//! #[derive(Clone)]
+//! struct Logger<S>(S);
+//!
+//! impl<S: RpcServiceT + Send + Sync> RpcServiceT for Logger<S> {
+//!     type MethodResponse = S::MethodResponse;
+//!     type NotificationResponse = S::NotificationResponse;
+//!     type BatchResponse = S::BatchResponse;
+//!
+//!     fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+//!         if let Some(method) = MethodMetadata::get(request.extensions()) {
+//!             println!("calling {} (deprecated: {:?})", method.name, method.deprecated);
+//!         }
+//!         self.0.call(request)
+//!     }
+//!
+//!     fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+//!         self.0.batch(batch)
+//!     }
+//!
+//!     fn notification<'a>(&self, n: Notification<'a>) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+//!         self.0.notification(n)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mut module = SpecModule::new(());
+//!     module.layer_for_tag("admin", tower::layer::layer_fn(Logger));
+//!
+//!     let server = Server::builder()
+//!         .set_rpc_middleware(RpcServiceBuilder::new().layer(module.rpc_middleware()))
+//!         .build("127.0.0.1:0")
+//!         .await?;
+//!     let handle = server.start(module.into_jsonrpsee_module());
+//!     Ok(())
+//! }
+//! ```
+use crate::spec;
+use jsonrpsee::{
+    Extensions,
+    core::{TEN_MB_SIZE_BYTES, server::BatchResponseBuilder},
+    server::middleware::rpc::{
+        Batch, BatchEntry, MethodResponse, Notification, Request, RpcServiceT,
+    },
+};
+use std::{collections::HashMap, future::Future, ops::Deref, pin::Pin, sync::Arc};
+
+type BoxFuture<'a> = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+/// The specification of the method being called, inserted into the extensions of every request
+/// (and notification) to a method of a [`crate::SpecModule`] by its [`SpecLayer`].
+#[derive(Debug, Clone)]
+pub struct MethodMetadata(Arc<spec::Method>);
+
+impl MethodMetadata {
+    pub(crate) fn new(method: spec::Method) -> Self {
+        MethodMetadata(Arc::new(method))
+    }
+
+    /// Returns the specification of the method being called, if known.
+    pub fn get(extensions: &Extensions) -> Option<&spec::Method> {
+        extensions
+            .get::<MethodMetadata>()
+            .map(|metadata| &*metadata.0)
+    }
+}
+
+impl Deref for MethodMetadata {
+    type Target = spec::Method;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The object safe part of [`RpcServiceT`], so services of any type can be stacked at runtime.
+trait ErasedRpcService: Send + Sync {
+    fn call_boxed<'a>(&self, request: Request<'a>) -> BoxFuture<'a>;
+    fn notification_boxed<'a>(&self, notification: Notification<'a>) -> BoxFuture<'a>;
+    fn batch_boxed<'a>(&self, batch: Batch<'a>) -> BoxFuture<'a>;
+}
+
+impl<S> ErasedRpcService for S
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync,
+{
+    fn call_boxed<'a>(&self, request: Request<'a>) -> BoxFuture<'a> {
+        Box::pin(RpcServiceT::call(self, request))
+    }
+
+    fn notification_boxed<'a>(&self, notification: Notification<'a>) -> BoxFuture<'a> {
+        Box::pin(RpcServiceT::notification(self, notification))
+    }
+
+    fn batch_boxed<'a>(&self, batch: Batch<'a>) -> BoxFuture<'a> {
+        Box::pin(RpcServiceT::batch(self, batch))
+    }
+}
+
+/// An RPC service of any type, which the layers of an [`RpcLayer`] wrap.
+#[derive(Clone)]
+pub struct BoxedRpcService(Arc<dyn ErasedRpcService>);
+
+impl BoxedRpcService {
+    /// Boxes the service.
+    pub fn new<S>(service: S) -> Self
+    where
+        S: RpcServiceT<
+                MethodResponse = MethodResponse,
+                NotificationResponse = MethodResponse,
+                BatchResponse = MethodResponse,
+            > + Send
+            + Sync
+            + 'static,
+    {
+        BoxedRpcService(Arc::new(service))
+    }
+}
+
+impl RpcServiceT for BoxedRpcService {
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.0.call_boxed(request)
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.0.batch_boxed(batch)
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.0.notification_boxed(notification)
+    }
+}
+
+/// A `tower::Layer` of RPC services of any type, to be scoped to some methods of a
+/// [`crate::SpecModule`].
+#[derive(Clone)]
+pub struct RpcLayer(Arc<dyn Fn(BoxedRpcService) -> BoxedRpcService + Send + Sync>);
+
+impl RpcLayer {
+    /// Boxes the layer.
+    pub fn new<L>(layer: L) -> Self
+    where
+        L: tower::Layer<BoxedRpcService> + Send + Sync + 'static,
+        L::Service: RpcServiceT<
+                MethodResponse = MethodResponse,
+                NotificationResponse = MethodResponse,
+                BatchResponse = MethodResponse,
+            > + Send
+            + Sync
+            + 'static,
+    {
+        RpcLayer(Arc::new(move |inner| {
+            BoxedRpcService::new(layer.layer(inner))
+        }))
+    }
+}
+
+impl tower::Layer<BoxedRpcService> for RpcLayer {
+    type Service = BoxedRpcService;
+
+    fn layer(&self, inner: BoxedRpcService) -> Self::Service {
+        (self.0)(inner)
+    }
+}
+
+/// Layer for [`Server::set_rpc_middleware`](jsonrpsee::server::Builder::set_rpc_middleware)
+/// applying the layers scoped to each method of a [`crate::SpecModule`], created by
+/// [`crate::SpecModule::rpc_middleware`].
+#[derive(Clone)]
+pub struct SpecLayer {
+    methods: Arc<HashMap<String, (MethodMetadata, Vec<RpcLayer>)>>,
+    max_response_body_size: u32,
+}
+
+impl Default for SpecLayer {
+    fn default() -> Self {
+        SpecLayer::new(HashMap::new())
+    }
+}
+
+impl SpecLayer {
+    pub(crate) fn new(methods: HashMap<String, (MethodMetadata, Vec<RpcLayer>)>) -> Self {
+        SpecLayer {
+            methods: Arc::new(methods),
+            max_response_body_size: TEN_MB_SIZE_BYTES,
+        }
+    }
+
+    /// Sets the maximum size of the responses to batches, as the entries of a batch are called
+    /// by this layer. It should be the `max_response_body_size` of the server, whose default
+    /// (10 MiB) is the default.
+    pub fn max_response_body_size(mut self, max_response_body_size: u32) -> Self {
+        self.max_response_body_size = max_response_body_size;
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for SpecLayer
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync
+        + 'static,
+{
+    type Service = SpecService;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let inner = BoxedRpcService::new(inner);
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, (metadata, layers))| {
+                // The first layer added is the outermost
+                let service = layers.iter().rev().fold(inner.clone(), |service, layer| {
+                    tower::Layer::layer(layer, service)
+                });
+                (name.clone(), (metadata.clone(), service))
+            })
+            .collect();
+
+        SpecService {
+            inner,
+            methods: Arc::new(methods),
+            max_response_body_size: self.max_response_body_size,
+        }
+    }
+}
+
+/// The RPC service created by [`SpecLayer`].
+#[derive(Clone)]
+pub struct SpecService {
+    inner: BoxedRpcService,
+    methods: Arc<HashMap<String, (MethodMetadata, BoxedRpcService)>>,
+    max_response_body_size: u32,
+}
+
+impl RpcServiceT for SpecService {
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(
+        &self,
+        mut request: Request<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        match self.methods.get(request.method_name()) {
+            Some((metadata, service)) => {
+                request.extensions_mut().insert(metadata.clone());
+                service.call(request)
+            }
+            None => self.inner.call(request),
+        }
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let service = self.clone();
+
+        async move {
+            // Every entry goes through the layers of its own method
+            let mut responses =
+                BatchResponseBuilder::new_with_limit(service.max_response_body_size as usize);
+            for entry in batch {
+                let response = match entry {
+                    Ok(BatchEntry::Call(request)) => service.call(request).await,
+                    Ok(BatchEntry::Notification(notification)) => {
+                        service.notification(notification).await;
+                        continue;
+                    }
+                    Err(error) => {
+                        let (error, id) = error.into_parts();
+                        MethodResponse::error(id, error)
+                    }
+                };
+                if let Err(response) = responses.append(response) {
+                    return response;
+                }
+            }
+
+            if responses.is_empty() {
+                MethodResponse::notification()
+            } else {
+                MethodResponse::from_batch(responses.finish())
+            }
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        mut notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        match self.methods.get(notification.method_name()) {
+            Some((metadata, service)) => {
+                notification.extensions_mut().insert(metadata.clone());
+                service.notification(notification)
+            }
+            None => self.inner.notification(notification),
+        }
+    }
+}
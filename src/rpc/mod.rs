@@ -1,5 +1,23 @@
+mod auth;
+mod deprecation;
+mod limit;
 mod method;
+mod middleware;
 mod module;
+mod notification;
+mod page;
+mod service;
+mod stream;
+mod timeout;
 
+pub use auth::*;
+pub use deprecation::*;
+pub use limit::*;
 pub use method::*;
+pub use middleware::*;
 pub use module::*;
+pub use notification::*;
+pub use page::*;
+pub use service::*;
+pub use stream::*;
+pub use timeout::*;
@@ -2,10 +2,21 @@
 //! creating and managing JSON-RPC modules using the `jsonrpsee` library. It allows for
 //! openspec_ registration of synchronous and asynchronous RPC methods, as well as conversion
 //! into a `jsonrpsee::RpcModule`.
-use std::path::Path;
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
 
-use crate::{OpenRpcSpec, RpcMethod, ServerHandler, spec};
-use jsonrpsee::core::RegisterMethodError;
+use crate::{
+    AuthorizationGuard, Authorizer, AuthorizerSlot, BoxedRpcService, CallTimeout,
+    DefaultTimeoutSlot, DeprecatedCall, DeprecatedCallHookSlot, DeprecationNotifier, LimitGuard,
+    Limits, MethodMetadata, OpenRpcSpec, Permit, RpcLayer, RpcMethod, RpcService, ServerHandler,
+    SpecLayer, SpecValidationError, document::validate_generated, method_timeout, pipe,
+    record_subscription, record_timeout, spec, static_unsubscribe_method_name,
+};
+use jsonrpsee::{
+    Extensions,
+    core::RegisterMethodError,
+    server::middleware::rpc::{MethodResponse, RpcServiceT},
+    types::ErrorObjectOwned,
+};
 use serde::Serialize;
 
 /// A wrapper around `jsonrpsee::RpcModule` that simplifies the process of
@@ -50,6 +61,27 @@ pub struct SpecModule<Context = ()> {
     module: jsonrpsee::RpcModule<Context>,
     /// OpenRPC Specification
     spec: OpenRpcSpec,
+    /// Hook invoked on calls to deprecated methods, shared with their handlers
+    deprecated_call_hook: DeprecatedCallHookSlot,
+    /// Authorizer of calls to methods requiring scopes, shared with their handlers
+    authorizer: AuthorizerSlot,
+    /// Layers scoped to some methods, applied by the layer of `rpc_middleware`
+    layers: Vec<(LayerScope, RpcLayer)>,
+    /// Limits of the methods not set by their own limits
+    default_limits: Limits,
+    /// Limits set by the methods themselves, e.g. with `#[rpc(rate_limit = "100/s")]`, and the
+    /// guards enforcing them, shared with their handlers
+    method_limits: HashMap<String, (Limits, LimitGuard)>,
+    /// Timeout of the async methods not set by their own timeout, shared with their handlers
+    default_timeout: DefaultTimeoutSlot,
+    /// Timeouts set by the async methods themselves, e.g. with `#[rpc(timeout = "5s")]`
+    method_timeouts: HashMap<String, Option<Duration>>,
+}
+
+/// The methods a layer is scoped to.
+enum LayerScope {
+    Method(String),
+    Tag(String),
 }
 
 impl<Context: Send + Sync + 'static> SpecModule<Context> {
@@ -64,6 +96,13 @@ impl<Context: Send + Sync + 'static> SpecModule<Context> {
         SpecModule {
             module: jsonrpsee::RpcModule::new(context),
             spec: OpenRpcSpec::builder().build(),
+            deprecated_call_hook: Default::default(),
+            authorizer: Default::default(),
+            layers: Vec::new(),
+            default_limits: Limits::default(),
+            method_limits: HashMap::new(),
+            default_timeout: Default::default(),
+            method_timeouts: HashMap::new(),
         }
     }
 
@@ -82,6 +121,77 @@ impl<Context: Send + Sync + 'static> SpecModule<Context> {
         self.spec.external_docs = Some(external_docs);
     }
 
+    /// Declares a tag once in the OpenRPC Specification's components.
+    ///
+    /// Methods tagged with the same name (e.g. `#[rpc(tags = ["users"])]`) reference this
+    /// declaration from `components.tags` instead of inlining the tag, regardless of whether
+    /// they were added before or after the tag was declared.
+    pub fn add_spec_tag(&mut self, tag: spec::Tag) {
+        for method in self.spec.methods.iter_mut() {
+            reference_declared_tag(method, &tag.name);
+        }
+
+        self.spec
+            .components
+            .get_or_insert_with(|| spec::Components::builder().build())
+            .tags
+            .get_or_insert_with(Default::default)
+            .insert(tag.name.clone(), tag);
+    }
+
+    /// Sets a hook invoked on every call to a deprecated method (e.g. to log a warning or record
+    /// a metric). This applies to all deprecated methods, whether added before or after this call.
+    pub fn on_deprecated_call(&mut self, hook: impl Fn(&DeprecatedCall) + Send + Sync + 'static) {
+        *self
+            .deprecated_call_hook
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+    }
+
+    /// Sets the authorizer of calls to methods requiring scopes (e.g. `#[rpc(auth = "admin")]`).
+    /// This applies to all such methods, whether added before or after this call.
+    ///
+    /// Until an authorizer is set, calls to methods requiring scopes are denied. Denied calls are
+    /// responded to with the [`UNAUTHORIZED_CODE`](crate::UNAUTHORIZED_CODE) error.
+    pub fn set_authorizer(&mut self, authorizer: impl Authorizer) {
+        *self.authorizer.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(authorizer));
+    }
+
+    /// Sets the limits of the methods that don't set their own (e.g. with
+    /// `#[rpc(rate_limit = "100/s", max_concurrency = 8)]`), each limit falling back separately.
+    /// This applies to all methods, whether added before or after this call, and is recorded in
+    /// the spec.
+    ///
+    /// The calls already counted by the limits of the methods are counted anew.
+    pub fn set_default_limits(&mut self, limits: Limits) {
+        self.default_limits = limits;
+        for method in self.spec.methods.iter_mut() {
+            if let Some((own_limits, guard)) = self.method_limits.get(&method.name) {
+                let limits = own_limits.or(limits);
+                limits.record(method);
+                guard.set(limits);
+            }
+        }
+    }
+
+    /// Sets the timeout of the async methods that don't set their own (e.g. with
+    /// `#[rpc(timeout = "5s")]`), or removes it with `None`. This applies to all async methods,
+    /// whether added before or after this call, and is recorded in the spec.
+    ///
+    /// Calls exceeding their timeout are responded to with the [`TIMEOUT_CODE`](crate::TIMEOUT_CODE)
+    /// error. Sync methods can't be interrupted, so they have no timeout.
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        *self
+            .default_timeout
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = timeout;
+        for method in self.spec.methods.iter_mut() {
+            if let Some(own_timeout) = self.method_timeouts.get(&method.name) {
+                record_timeout(method, own_timeout.or(timeout));
+            }
+        }
+    }
+
     /// Returns a reference to the OpenRPC Specification (semver 2.0.0).
     pub fn spec(&self) -> &OpenRpcSpec {
         &self.spec
@@ -92,6 +202,18 @@ impl<Context: Send + Sync + 'static> SpecModule<Context> {
         std::fs::write(filepath, self.spec.to_string_pretty())
     }
 
+    /// Checks the OpenRPC Specification built so far for problems of its generation: params of
+    /// type `()`, results of the same name, methods or a document that don't read back as written,
+    /// the info left to its defaults, and server URLs with undeclared `{variables}`. With the
+    /// `validate` feature, the document is also validated, see `OpenRpcSpec::validate`.
+    ///
+    /// # Returns
+    /// - `Ok(())`: No problem was found.
+    /// - `Err(SpecValidationError)`: The diagnostics of every problem found.
+    pub fn validate_spec(&self) -> Result<(), SpecValidationError> {
+        validate_generated(&self.spec)
+    }
+
     /// Adds a new RPC method to the module.
     ///
     /// # Type Parameters
@@ -109,20 +231,182 @@ impl<Context: Send + Sync + 'static> SpecModule<Context> {
         &mut self,
         method: impl RpcMethod<Context, T>,
     ) -> Result<&mut Self, RegisterMethodError> {
-        self.spec.methods.push(method.spec());
+        let mut method_spec = method.spec();
+        let declared_tags = self
+            .spec
+            .components
+            .as_ref()
+            .and_then(|components| components.tags.as_ref());
+        for name in declared_tags.into_iter().flat_map(|tags| tags.keys()) {
+            reference_declared_tag(&mut method_spec, name);
+        }
+        let own_limits = Limits::of(&method_spec);
+        let limits = own_limits.or(self.default_limits);
+        limits.record(&mut method_spec);
+        let limit_guard = LimitGuard::default();
+        limit_guard.set(limits);
+
+        let guard = CallGuard::new(
+            &method_spec,
+            &self.authorizer,
+            &self.deprecated_call_hook,
+            limit_guard.clone(),
+        );
 
         match method.handler() {
             ServerHandler::Sync(handler) => {
-                self.module.register_method(method.name(), handler)?;
+                self.module
+                    .register_method(method.name(), move |params, context, ext| {
+                        let _permit = guard.check(ext)?;
+                        handler(params, context, ext)
+                    })?;
+            }
+            ServerHandler::Blocking(handler) => {
+                self.module.register_blocking_method(
+                    method.name(),
+                    move |params, context, ext| {
+                        let _permit = guard.check(&ext)?;
+                        handler(params, context, ext)
+                    },
+                )?;
             }
             ServerHandler::Async(handler) => {
-                self.module.register_async_method(method.name(), handler)?;
+                let own_timeout = method_timeout(&method_spec);
+                let default_timeout = *self
+                    .default_timeout
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner());
+                record_timeout(&mut method_spec, own_timeout.or(default_timeout));
+
+                // Every call is run within the timeout (if any) and given a cancellation token
+                let timeout = CallTimeout {
+                    timeout: own_timeout,
+                    default_timeout: self.default_timeout.clone(),
+                };
+                self.module
+                    .register_async_method(
+                        method.name(),
+                        move |params, context, ext| match guard.check(&ext) {
+                            Ok(permit) => timeout.run(ext, |ext| {
+                                let call = handler(params, context, ext);
+                                async move {
+                                    let _permit = permit;
+                                    call.await
+                                }
+                            }),
+                            Err(error) => Box::pin(async move { Err(error) }),
+                        },
+                    )?;
+                self.method_timeouts
+                    .insert(method.name().to_string(), own_timeout);
+            }
+            ServerHandler::Stream(handler) => {
+                record_subscription(&mut method_spec);
+                let unsubscribe = static_unsubscribe_method_name(method.name());
+                self.module.register_subscription(
+                    method.name(),
+                    method.name(),
+                    unsubscribe,
+                    move |params, pending, context, ext| {
+                        let checked = guard.check(&ext);
+                        async move {
+                            // The permit is held until the subscription is closed
+                            let (stream, _permit) = match checked {
+                                Ok(permit) => (handler(params, context, ext).await, Some(permit)),
+                                Err(error) => (Err(error), None),
+                            };
+                            pipe(pending, stream).await
+                        }
+                    },
+                )?;
             }
         }
 
+        self.method_limits
+            .insert(method.name().to_string(), (own_limits, limit_guard));
+        for layer in method.middleware() {
+            self.layers
+                .push((LayerScope::Method(method.name().to_string()), layer));
+        }
+        self.spec.methods.push(method_spec);
         Ok(self)
     }
 
+    /// Attaches an RPC middleware layer to a single method (registered before or after).
+    ///
+    /// Layers are applied in the order they're added, the first being the outermost, and only
+    /// take effect with the layer of [`SpecModule::rpc_middleware`] set on the server.
+    pub fn layer_for_method<L>(&mut self, method: &str, layer: L) -> &mut Self
+    where
+        L: tower::Layer<BoxedRpcService> + Send + Sync + 'static,
+        L::Service: RpcServiceT<
+                MethodResponse = MethodResponse,
+                NotificationResponse = MethodResponse,
+                BatchResponse = MethodResponse,
+            > + Send
+            + Sync
+            + 'static,
+    {
+        self.layers
+            .push((LayerScope::Method(method.to_string()), RpcLayer::new(layer)));
+        self
+    }
+
+    /// Attaches an RPC middleware layer to every method with the tag (registered before or after).
+    ///
+    /// Layers are applied in the order they're added, the first being the outermost, and only
+    /// take effect with the layer of [`SpecModule::rpc_middleware`] set on the server.
+    pub fn layer_for_tag<L>(&mut self, tag: &str, layer: L) -> &mut Self
+    where
+        L: tower::Layer<BoxedRpcService> + Send + Sync + 'static,
+        L::Service: RpcServiceT<
+                MethodResponse = MethodResponse,
+                NotificationResponse = MethodResponse,
+                BatchResponse = MethodResponse,
+            > + Send
+            + Sync
+            + 'static,
+    {
+        self.layers
+            .push((LayerScope::Tag(tag.to_string()), RpcLayer::new(layer)));
+        self
+    }
+
+    /// Returns the layer for
+    /// [`Server::set_rpc_middleware`](jsonrpsee::server::Builder::set_rpc_middleware) that applies
+    /// the layers scoped to each method, and inserts the [`MethodMetadata`] of the method called
+    /// into the extensions of every request.
+    ///
+    /// The layer calls the entries of batches itself, so a server with a `max_response_body_size`
+    /// other than the default must set it with [`SpecLayer::max_response_body_size`] too.
+    ///
+    /// The layer holds the methods and layers added so far, so it's best created right before
+    /// starting the server.
+    pub fn rpc_middleware(&self) -> SpecLayer {
+        let methods = self
+            .spec
+            .methods
+            .iter()
+            .map(|method| {
+                let tags = method.tag_names();
+                let layers = self
+                    .layers
+                    .iter()
+                    .filter(|(scope, _)| match scope {
+                        LayerScope::Method(name) => *name == method.name,
+                        LayerScope::Tag(tag) => tags.contains(&tag.as_str()),
+                    })
+                    .map(|(_, layer)| layer.clone())
+                    .collect();
+                (
+                    method.name.clone(),
+                    (MethodMetadata::new(method.clone()), layers),
+                )
+            })
+            .collect();
+        SpecLayer::new(methods)
+    }
+
     /// Consumes the `SpecModule` and converts it into a `jsonrpsee::RpcModule`.
     ///
     /// # Returns
@@ -132,6 +416,82 @@ impl<Context: Send + Sync + 'static> SpecModule<Context> {
     }
 }
 
+/// Checks made on every call to a method before its handler runs.
+#[derive(Clone)]
+struct CallGuard {
+    authorization: Option<AuthorizationGuard>,
+    limits: LimitGuard,
+    deprecation: Option<DeprecationNotifier>,
+}
+
+impl CallGuard {
+    /// Returns the guard of the method.
+    fn new(
+        method: &spec::Method,
+        authorizer: &AuthorizerSlot,
+        deprecated_call_hook: &DeprecatedCallHookSlot,
+        limits: LimitGuard,
+    ) -> Self {
+        let scopes: Vec<String> = method
+            .required_scopes()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let requires_scopes = !scopes.is_empty();
+        let deprecated = method.deprecated.unwrap_or_default();
+
+        let method = Arc::new(method.clone());
+        CallGuard {
+            authorization: requires_scopes.then(|| AuthorizationGuard {
+                method: method.clone(),
+                scopes: scopes.into(),
+                authorizer: authorizer.clone(),
+            }),
+            deprecation: deprecated.then(|| DeprecationNotifier {
+                method,
+                hook: deprecated_call_hook.clone(),
+            }),
+            limits,
+        }
+    }
+
+    /// Authorizes the call and admits it within the limits of the method, then notifies the hook
+    /// if the method is deprecated.
+    ///
+    /// # Returns
+    /// - `Ok(Permit)`: The permit to hold while the call is handled.
+    /// - `Err(ErrorObjectOwned)`: The error to respond with.
+    fn check(&self, extensions: &Extensions) -> Result<Permit, ErrorObjectOwned> {
+        if let Some(authorization) = &self.authorization {
+            authorization.authorize(extensions)?;
+        }
+        let permit = self.limits.admit()?;
+        if let Some(deprecation) = &self.deprecation {
+            deprecation.notify(extensions);
+        }
+        Ok(permit)
+    }
+}
+
+/// Replaces any inline tag named `name` on the method with a reference to `components.tags`.
+fn reference_declared_tag(method: &mut spec::Method, name: &str) {
+    for tag in method.tags.iter_mut().flatten() {
+        if matches!(tag, spec::ReferenceOr::Item(inline) if inline.name == name) {
+            *tag = spec::ReferenceOr::Reference(spec::Reference::tag(name));
+        }
+    }
+}
+
+impl<Service: RpcService> SpecModule<Service> {
+    /// Creates a new `SpecModule` with the service as its context, registering every method of
+    /// its `impl` block marked `#[rpc]`.
+    pub fn from_service(service: Service) -> Result<Self, RegisterMethodError> {
+        let mut module = SpecModule::new(service);
+        Service::register_methods(&mut module)?;
+        Ok(module)
+    }
+}
+
 impl<Context: Send + Sync + 'static> From<SpecModule<Context>> for jsonrpsee::RpcModule<Context> {
     fn from(val: SpecModule<Context>) -> Self {
         val.into_jsonrpsee_module()
@@ -0,0 +1,109 @@
+//! This module defines the RPC middleware that dispatches JSON-RPC notifications to methods.
+//!
+//! The `jsonrpsee` server drops notifications (calls without an `id`) without calling any method,
+//! so methods declared with `#[rpc(notification)]` are only called with this middleware in place.
+//!
+//! # Example
+//! ```
+//! use openspec_jsonrpsee::NotificationLayer;
+//! use jsonrpsee::server::{Server, middleware::rpc::RpcServiceBuilder};
+//!
+//! let server = Server::builder()
+//!     .set_rpc_middleware(RpcServiceBuilder::new().layer(NotificationLayer));
+//! ```
+use jsonrpsee::{
+    server::middleware::rpc::{
+        Batch, BatchEntry, MethodResponse, Notification, Request, RpcServiceT,
+    },
+    types::Id,
+};
+use std::future::Future;
+
+/// Layer for [`Server::set_rpc_middleware`](jsonrpsee::server::Builder::set_rpc_middleware) that
+/// calls the method of a notification (also in batches), discarding the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotificationLayer;
+
+impl<S> tower::Layer<S> for NotificationLayer {
+    type Service = NotificationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NotificationService { inner }
+    }
+}
+
+/// The RPC service created by [`NotificationLayer`].
+#[derive(Debug, Clone)]
+pub struct NotificationService<S> {
+    inner: S,
+}
+
+impl<S> RpcServiceT for NotificationService<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.inner.call(request)
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let service = self.clone();
+
+        async move {
+            // Notifications are called here as the inner service would drop them
+            let mut calls = Vec::new();
+            let mut got_notification = false;
+            for entry in batch {
+                match entry {
+                    Ok(BatchEntry::Notification(notification)) => {
+                        got_notification = true;
+                        service.notification(notification).await;
+                    }
+                    entry => calls.push(entry),
+                }
+            }
+
+            if calls.is_empty() && got_notification {
+                MethodResponse::notification()
+            } else {
+                service.inner.batch(Batch::from(calls)).await
+            }
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let Notification {
+            method,
+            params,
+            extensions,
+            ..
+        } = notification;
+        let mut request = Request::owned(
+            method.into_owned(),
+            params.map(|params| params.into_owned()),
+            Id::Null,
+        );
+        request.extensions = extensions.clone();
+        let call = self.inner.call(request);
+
+        async move {
+            // The notification should never be responded to, whatever the method returns
+            call.await;
+            MethodResponse::notification().with_extensions(extensions)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! This module defines the cursor pagination of methods marked `#[rpc(paginated)]`, returning a
+//! [`Page`] of items and taking a [`PageRequest`] as their last argument.
+//!
+//! The page request is sent as the `cursor` and `limit` params, `cursor` being `null` for the first
+//! page. The specification of the method links to the next page (`next_page`), called with the
+//! `next_cursor` of the result. Clients can stream the items of all the pages with the generated
+//! `paginate` function, following the cursors (see [`paginate`]).
+//!
+//! # Example
+//! ```
+//! use openspec_jsonrpsee::{Page, PageRequest, rpc};
+//!
+//! #[rpc(paginated)]
+//! fn list_numbers(odd: bool, page: PageRequest<u32>) -> Page<u32, u32> {
+//!     let start = page.cursor.unwrap_or(0);
+//!     let end = start + page.limit_or(10);
+//!     let items = (start..end.min(100)).filter(|n| n % 2 == odd as u32).collect();
+//!     Page::new(items, (end < 100).then_some(end))
+//! }
+//! ```
+use serde::{Deserialize, Serialize};
+
+/// A page of items, with the cursor of the next page.
+///
+/// # Type Parameters
+/// - `T`: The type of the items.
+/// - `Cursor`: The type of the cursors, opaque to the clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+pub struct Page<T, Cursor = String> {
+    /// The items of the page.
+    pub items: Vec<T>,
+    /// The cursor of the next page, `None` on the last page.
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T, Cursor> Page<T, Cursor> {
+    /// Creates a page of items, followed by the page of the cursor (if any).
+    pub fn new(items: Vec<T>, next_cursor: Option<Cursor>) -> Self {
+        Page { items, next_cursor }
+    }
+
+    /// Creates the last page of items.
+    pub fn last(items: Vec<T>) -> Self {
+        Page::new(items, None)
+    }
+}
+
+/// The page requested from a paginated method, sent as its `cursor` and `limit` params.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+pub struct PageRequest<Cursor = String> {
+    /// The cursor of the page, `None` for the first page.
+    pub cursor: Option<Cursor>,
+    /// The number of items requested, up to the method to cap.
+    pub limit: Option<u32>,
+}
+
+impl<Cursor> PageRequest<Cursor> {
+    /// Requests the first page.
+    pub fn first(limit: Option<u32>) -> Self {
+        PageRequest {
+            cursor: None,
+            limit,
+        }
+    }
+
+    /// Returns the number of items requested, or the default if none was requested.
+    pub fn limit_or(&self, default: u32) -> u32 {
+        self.limit.unwrap_or(default)
+    }
+}
+
+/// Streams the items of all the pages, fetched with `fetch` from the first page and then with the
+/// cursor of the previous page, until the last page. The stream ends after the first error, or with
+/// an error if a page has the cursor it was fetched with as its next cursor, as it would be fetched
+/// forever.
+#[cfg(feature = "client")]
+pub fn paginate<T, Cursor, F, Fut>(
+    limit: Option<u32>,
+    fetch: F,
+) -> impl futures_util::Stream<Item = Result<T, jsonrpsee::core::ClientError>>
+where
+    Cursor: Clone + PartialEq,
+    F: FnMut(PageRequest<Cursor>) -> Fut,
+    Fut: Future<Output = Result<Page<T, Cursor>, jsonrpsee::core::ClientError>>,
+{
+    use futures_util::{StreamExt, stream};
+
+    // The cursor of the page to fetch, `None` once the last page was fetched
+    let first: Option<Option<Cursor>> = Some(None);
+    stream::unfold((first, fetch), move |(cursor, mut fetch)| async move {
+        let cursor = cursor?;
+        let page = fetch(PageRequest {
+            cursor: cursor.clone(),
+            limit,
+        })
+        .await;
+        let (mut items, next): (Vec<_>, _) = match page {
+            Ok(page) => (
+                page.items.into_iter().map(Ok).collect(),
+                page.next_cursor.map(Some),
+            ),
+            Err(error) => (vec![Err(error)], None),
+        };
+        let next = match next {
+            Some(next) if next.is_some() && next == cursor => {
+                items.push(Err(jsonrpsee::core::ClientError::Custom(
+                    "the next cursor is the cursor of the page, which would be fetched forever"
+                        .into(),
+                )));
+                None
+            }
+            next => next,
+        };
+        Some((stream::iter(items), (next, fetch)))
+    })
+    .flatten()
+}
@@ -0,0 +1,51 @@
+//! This module defines the trait implemented by `#[rpc]` on an `impl` block, whose methods taking
+//! `&self` are registered all at once into a [`crate::SpecModule`] of the service.
+//!
+//! The struct of each method is prefixed with the service, e.g. `CounterIncrement` for `increment`,
+//! so services can have methods of a same name, e.g. `list`. `Self` names the service.
+//!
+//! # Example
+//! ```
+//! use openspec_jsonrpsee::{SpecModule, rpc};
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//!
+//! struct Counter {
+//!     count: AtomicU64,
+//! }
+//!
+//! #[rpc]
+//! impl Counter {
+//!     /// Increments the counter, returning its new value
+//!     #[rpc]
+//!     fn increment(&self, by: u64) -> u64 {
+//!         self.count.fetch_add(by, Ordering::Relaxed) + by
+//!     }
+//!
+//!     #[rpc(tags = ["read"])]
+//!     async fn count(&self) -> u64 {
+//!         self.count.load(Ordering::Relaxed)
+//!     }
+//!
+//!     // Not marked, so not a method of the service
+//!     fn reset(&self) {
+//!         self.count.store(0, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), jsonrpsee::core::RegisterMethodError> {
+//! let module = SpecModule::from_service(Counter { count: AtomicU64::new(0) })?;
+//! assert_eq!(module.spec().methods.len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+use crate::SpecModule;
+use jsonrpsee::core::RegisterMethodError;
+
+/// A service whose methods are registered with [`SpecModule::from_service`].
+///
+/// You **aren't** expected to implement this trait directly. Instead, use the provided
+/// [`crate::rpc`] macro on the `impl` block of the service, marking its methods with `#[rpc]`.
+pub trait RpcService: Send + Sync + Sized + 'static {
+    /// Registers the methods of the service into the module.
+    fn register_methods(module: &mut SpecModule<Self>) -> Result<(), RegisterMethodError>;
+}
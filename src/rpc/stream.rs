@@ -0,0 +1,150 @@
+//! This module defines streamed results, returned as an [`RpcStream`] by methods delivering large
+//! lists (e.g. exports or logs) item by item rather than materialized in one response.
+//!
+//! A method returning an `RpcStream<T>` is registered as a subscription, so it's only available
+//! over WebSocket: the call is responded to with the id of the subscription, then each item is sent
+//! as a notification named after the method, until the stream ends or the client unsubscribes with
+//! the [`unsubscribe_method_name`] method. The subscription is closed once the stream ends, or with
+//! the error of an item that failed.
+//!
+//! The subscription is recorded in the specification of the method (`x-subscription`), its result
+//! describing the items.
+//!
+//! # Example
+//! ```
+//! use openspec_jsonrpsee::{RpcStream, rpc};
+//!
+//! #[rpc]
+//! fn export_logs(count: usize) -> RpcStream<String> {
+//!     RpcStream::iter((0..count).map(|i| format!("log {i}")))
+//! }
+//! ```
+use crate::spec;
+use futures_util::{Stream, StreamExt, stream::BoxStream};
+use jsonrpsee::{
+    PendingSubscriptionSink,
+    core::{RpcResult, SubscriptionError},
+    server::SubscriptionCloseResponse,
+};
+use serde::Serialize;
+use serde_json::{json, value::to_raw_value};
+use std::{
+    collections::HashSet,
+    sync::{LazyLock, Mutex},
+};
+
+/// The items of a streamed result as responses, returned by the handler of the method.
+pub type ResponseStream<Response> = BoxStream<'static, Response>;
+
+/// The items of a streamed result, sent one by one to the subscriber of the method.
+pub struct RpcStream<T>(BoxStream<'static, RpcResult<T>>);
+
+impl<T: Send + 'static> RpcStream<T> {
+    /// Streams the items of the stream.
+    pub fn new(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        RpcStream(stream.map(Ok).boxed())
+    }
+
+    /// Streams the items of the stream, closing the subscription with the error of the first
+    /// item that failed.
+    pub fn try_new(stream: impl Stream<Item = RpcResult<T>> + Send + 'static) -> Self {
+        RpcStream(stream.boxed())
+    }
+
+    /// Streams the items of the iterator, produced as they're sent.
+    pub fn iter<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+    {
+        RpcStream::new(futures_util::stream::iter(items))
+    }
+
+    /// Returns the items, as results.
+    pub fn into_stream(self) -> ResponseStream<RpcResult<T>> {
+        self.0
+    }
+}
+
+/// Returns the name of the method unsubscribing from the method named `name`, e.g.
+/// `export_logs_unsubscribe`.
+pub fn unsubscribe_method_name(name: &str) -> String {
+    format!("{name}_unsubscribe")
+}
+
+/// Returns the [`unsubscribe_method_name`] with a static lifetime, as `jsonrpsee` requires for
+/// registering a subscription.
+///
+/// The names are interned process-wide, so each is leaked once however many modules register the
+/// method, e.g. one per test.
+pub(crate) fn static_unsubscribe_method_name(name: &str) -> &'static str {
+    static NAMES: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+
+    let name = unsubscribe_method_name(name);
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    match names.get(name.as_str()) {
+        Some(interned) => interned,
+        None => {
+            let interned = Box::leak(name.into_boxed_str());
+            names.insert(interned);
+            interned
+        }
+    }
+}
+
+/// Records the subscription in the specification of the method: the name of its notifications and
+/// of the method unsubscribing from it.
+pub(crate) fn record_subscription(method: &mut spec::Method) {
+    method.extensions.insert(
+        spec::extension::SUBSCRIPTION.to_string(),
+        json!({
+            "notification": method.name,
+            "unsubscribe": unsubscribe_method_name(&method.name),
+        }),
+    );
+}
+
+/// Accepts the subscription (or rejects it with the error) and sends the items to the subscriber,
+/// until the stream ends or the subscriber unsubscribes.
+pub(crate) async fn pipe<T: Serialize>(
+    pending: PendingSubscriptionSink,
+    stream: RpcResult<ResponseStream<RpcResult<T>>>,
+) -> SubscriptionCloseResponse {
+    let mut stream = match stream {
+        Ok(stream) => stream,
+        Err(error) => {
+            pending.reject(error).await;
+            return SubscriptionCloseResponse::None;
+        }
+    };
+    let Ok(sink) = pending.accept().await else {
+        return SubscriptionCloseResponse::None;
+    };
+
+    loop {
+        // Serialized as soon as produced, the items not being `Send`
+        let item = tokio::select! {
+            _ = sink.closed() => return SubscriptionCloseResponse::None,
+            item = stream.next() => item.map(|item| item.map(|item| to_raw_value(&item))),
+        };
+        let message = match item {
+            Some(Ok(message)) => message,
+            Some(Err(error)) => return close_with(&error),
+            None => return close_with(&()),
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(error) => return close_with(&error.to_string()),
+        };
+        if sink.send(message).await.is_err() {
+            return SubscriptionCloseResponse::None;
+        }
+    }
+}
+
+/// Closes the subscription with an error notification carrying the value (`null` once the stream
+/// ended), the only way the subscriber learns the subscription is closed.
+fn close_with(value: &impl Serialize) -> SubscriptionCloseResponse {
+    let value = to_raw_value(value).expect("the value should serialize to JSON");
+    SubscriptionCloseResponse::NotifErr(SubscriptionError::from_json(value))
+}
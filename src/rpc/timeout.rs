@@ -0,0 +1,245 @@
+//! This module defines the timeouts of async methods, set with `#[rpc(timeout = "5s")]` or for
+//! the whole module with [`crate::SpecModule::set_default_timeout`], and the cancellation of
+//! their calls.
+//!
+//! A call exceeding its timeout is responded to with the [`TIMEOUT_CODE`] error. Clients can
+//! shorten the timeout of their calls with a [`DeadlineHint`] in the extensions, e.g. from the
+//! [`DEADLINE_HEADER`] of HTTP requests with the [`DeadlineHeaderLayer`].
+//!
+//! Handlers can take a [`CancellationToken`] argument marked `#[cancellation]`, cancelled once the
+//! call times out or is dropped (e.g. when the connection closes), to stop the work they spawned
+//! cooperatively.
+use crate::spec;
+use jsonrpsee::{Extensions, core::RpcResult, server::HttpRequest, types::ErrorObjectOwned};
+use serde_json::json;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::Notify;
+
+/// The error code responded to calls exceeding their timeout.
+pub const TIMEOUT_CODE: i32 = -32002;
+/// The error message responded to calls exceeding their timeout.
+pub const TIMEOUT_MSG: &str = "Request timed out";
+
+/// The HTTP header holding the [`DeadlineHint`] of the calls of a request, e.g. `x-timeout: 500ms`.
+pub const DEADLINE_HEADER: &str = "x-timeout";
+
+/// Parses a duration like `500ms`, `5s`, `2m` or `1h`. A duration of zero isn't valid, as no
+/// call could ever complete within it.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value.parse().ok().filter(|value| *value > 0)?;
+    match unit.trim() {
+        "ms" => Some(Duration::from_millis(value)),
+        "s" => Some(Duration::from_secs(value)),
+        "m" => value.checked_mul(60).map(Duration::from_secs),
+        "h" => value.checked_mul(3600).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+/// Formats a duration like [`parse_duration`] parses it, in the largest exact unit.
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    match millis {
+        _ if millis == 0 || !millis.is_multiple_of(1000) => format!("{millis}ms"),
+        _ if millis.is_multiple_of(3_600_000) => format!("{}h", millis / 3_600_000),
+        _ if millis.is_multiple_of(60_000) => format!("{}m", millis / 60_000),
+        _ => format!("{}s", millis / 1000),
+    }
+}
+
+/// Returns the timeout recorded in the specification of the method (`x-timeout`), if any.
+pub(crate) fn method_timeout(method: &spec::Method) -> Option<Duration> {
+    method
+        .extensions
+        .get(spec::extension::TIMEOUT)
+        .and_then(|value| value.as_str())
+        .and_then(parse_duration)
+}
+
+/// Records the timeout in the specification of the method, replacing any timeout recorded.
+pub(crate) fn record_timeout(method: &mut spec::Method, timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => {
+            method.extensions.insert(
+                spec::extension::TIMEOUT.to_string(),
+                json!(format_duration(timeout)),
+            );
+        }
+        None => {
+            method.extensions.remove(spec::extension::TIMEOUT);
+        }
+    }
+}
+
+/// The longest a client is willing to wait for its call, shortening the timeout of the method.
+///
+/// It's read from the extensions of the call, where it can be inserted by a middleware, e.g. the
+/// [`DeadlineHeaderLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineHint(pub Duration);
+
+/// HTTP middleware for [`Server::set_http_middleware`](jsonrpsee::server::Builder::set_http_middleware)
+/// inserting a [`DeadlineHint`] into the extensions of the calls of requests with a valid
+/// [`DEADLINE_HEADER`].
+///
+/// For WebSocket connections, the header of the handshake applies to every call of the connection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeadlineHeaderLayer;
+
+impl<S> tower::Layer<S> for DeadlineHeaderLayer {
+    type Service = DeadlineHeaderService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeadlineHeaderService(inner)
+    }
+}
+
+/// The HTTP service created by [`DeadlineHeaderLayer`].
+#[derive(Debug, Clone)]
+pub struct DeadlineHeaderService<S>(S);
+
+impl<S, B> tower::Service<HttpRequest<B>> for DeadlineHeaderService<S>
+where
+    S: tower::Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        let hint = request
+            .headers()
+            .get(DEADLINE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_duration);
+        if let Some(hint) = hint {
+            request.extensions_mut().insert(DeadlineHint(hint));
+        }
+        self.0.call(request)
+    }
+}
+
+/// A token cancelled once the call it was created for times out or is dropped, injected into
+/// handlers through an argument marked `#[cancellation]`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<CancellationState>);
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Creates a token that isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking up every task waiting for it.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0.notify.notify_waiters();
+    }
+
+    /// Returns true if the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Waits until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            // Registered before checking, so a cancellation in between isn't missed
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Cancels the token when dropped, unless disarmed.
+struct CancelOnDrop(Option<CancellationToken>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(token) = self.0.take() {
+            token.cancel();
+        }
+    }
+}
+
+/// Shared slot for the default timeout, so it applies to methods registered both before and after
+/// it's set.
+pub(crate) type DefaultTimeoutSlot = Arc<RwLock<Option<Duration>>>;
+
+/// Runs the calls to one async method within its timeout, with a cancellation token.
+#[derive(Clone)]
+pub(crate) struct CallTimeout {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) default_timeout: DefaultTimeoutSlot,
+}
+
+impl CallTimeout {
+    pub(crate) fn run<T, F>(
+        &self,
+        mut extensions: Extensions,
+        call: impl FnOnce(Extensions) -> F,
+    ) -> Pin<Box<dyn Future<Output = RpcResult<T>> + Send>>
+    where
+        T: 'static,
+        F: Future<Output = RpcResult<T>> + Send + 'static,
+    {
+        let timeout = self.timeout.or(*self
+            .default_timeout
+            .read()
+            .unwrap_or_else(|e| e.into_inner()));
+        let hint = extensions.get::<DeadlineHint>().map(|hint| hint.0);
+        let timeout = match (timeout, hint) {
+            (Some(timeout), Some(hint)) => Some(timeout.min(hint)),
+            (timeout, hint) => timeout.or(hint),
+        };
+
+        let token = CancellationToken::new();
+        extensions.insert(token.clone());
+        let call = call(extensions);
+
+        Box::pin(async move {
+            let mut cancel_on_drop = CancelOnDrop(Some(token));
+            let response = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        return Err(ErrorObjectOwned::owned(
+                            TIMEOUT_CODE,
+                            TIMEOUT_MSG,
+                            Some(json!({ "timeoutMs": timeout.as_millis() as u64 })),
+                        ));
+                    }
+                },
+                None => call.await,
+            };
+            // The call completed, so there's nothing to cancel
+            cancel_on_drop.0 = None;
+            response
+        })
+    }
+}
@@ -0,0 +1,500 @@
+//! This module provides helpers to work with the JSON schemas of an OpenRPC document, i.e. to
+//! validate values against them and to synthesize values matching them.
+use crate::spec;
+use serde_json::{Map, Value};
+
+/// Limits how deep (recursive) schemas are followed when synthesizing a value.
+const MAX_DEPTH: usize = 16;
+
+/// Compiles a validator for the schema. OpenRPC schemas are JSON Schema draft 7.
+pub(crate) fn validator(schema: &Value) -> Result<jsonschema::Validator, String> {
+    jsonschema::draft7::new(schema).map_err(|e| e.to_string())
+}
+
+/// Returns every error of validating the value against the validator, as readable messages.
+pub(crate) fn errors(validator: &jsonschema::Validator, value: &Value) -> Vec<String> {
+    validator
+        .iter_errors(value)
+        .map(|error| match error.instance_path().as_str() {
+            "" => error.to_string(),
+            path => format!("{path}: {error}"),
+        })
+        .collect()
+}
+
+/// Validates the params of calls against the param schemas of a method, by position or by name.
+pub(crate) struct ParamsValidator {
+    params: Vec<ParamValidator>,
+}
+
+struct ParamValidator {
+    name: String,
+    required: bool,
+    validator: jsonschema::Validator,
+}
+
+impl ParamsValidator {
+    /// Compiles the param schemas of the method.
+    ///
+    /// # Returns
+    /// - `Ok(ParamsValidator)`: The validator of the method's params.
+    /// - `Err((String, String))`: The name of the param with an invalid schema, and why it's invalid.
+    pub(crate) fn new(method: &spec::Method) -> Result<Self, (String, String)> {
+        let params = method
+            .params
+            .iter()
+            .map(|param| {
+                Ok(ParamValidator {
+                    name: param.name.clone(),
+                    required: param.required.unwrap_or_default(),
+                    validator: validator(param.schema.as_value())
+                        .map_err(|message| (param.name.clone(), message))?,
+                })
+            })
+            .collect::<Result<_, (String, String)>>()?;
+        Ok(ParamsValidator { params })
+    }
+
+    /// Returns the names of the params, in order.
+    #[cfg_attr(not(feature = "mock"), allow(dead_code))]
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.params.iter().map(|param| param.name.as_str())
+    }
+
+    /// Returns the value of every param (in order), after validating them against their schemas.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Option<Value>>)`: The value of every param, `None` when it's omitted.
+    /// - `Err(Vec<String>)`: Every reason the params are invalid.
+    pub(crate) fn validate(&self, params: Value) -> Result<Vec<Option<Value>>, Vec<String>> {
+        let mut errors = Vec::new();
+
+        let values: Vec<Option<Value>> = match params {
+            Value::Null => vec![None; self.params.len()],
+            Value::Array(values) => {
+                if values.len() > self.params.len() {
+                    errors.push(format!(
+                        "expected at most {} params, got {}",
+                        self.params.len(),
+                        values.len()
+                    ));
+                }
+                let mut values = values.into_iter();
+                self.params.iter().map(|_| values.next()).collect()
+            }
+            Value::Object(mut values) => {
+                let ordered = self
+                    .params
+                    .iter()
+                    .map(|param| values.remove(&param.name))
+                    .collect();
+                errors.extend(values.keys().map(|name| format!("unknown param `{name}`")));
+                ordered
+            }
+            _ => return Err(vec!["params must be an array or object".into()]),
+        };
+
+        for (param, value) in self.params.iter().zip(&values) {
+            match value {
+                Some(value) => errors.extend(
+                    self::errors(&param.validator, value)
+                        .into_iter()
+                        .map(|error| format!("`{}`: {error}", param.name)),
+                ),
+                None if param.required && !param.validator.is_valid(&Value::Null) => {
+                    errors.push(format!("missing required param `{}`", param.name));
+                }
+                None => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Synthesizes a value matching the schema, preferring values the schema suggests itself
+/// (i.e. `const`, `examples`, `default` and `enum`).
+#[cfg_attr(not(feature = "mock"), allow(dead_code))]
+pub(crate) fn sample(schema: &Value) -> Value {
+    sample_at(schema, schema, 0)
+}
+
+fn sample_at(root: &Value, schema: &Value, depth: usize) -> Value {
+    let Some(schema) = schema.as_object() else {
+        // Boolean schemas accept anything (or nothing)
+        return Value::Null;
+    };
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+
+    if let Some(target) = resolve_ref(root, schema) {
+        return sample_at(root, target, depth + 1);
+    }
+    if let Some(value) = suggested_value(schema) {
+        return value.clone();
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if let Some(first) = schema.get(key).and_then(|s| s.as_array()?.first()) {
+            return sample_at(root, first, depth + 1);
+        }
+    }
+
+    match schema_type(schema) {
+        Some("object") => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let object: Map<String, Value> = properties
+                .into_iter()
+                .flatten()
+                .map(|(name, property)| (name.clone(), sample_at(root, property, depth + 1)))
+                .collect();
+            Value::Object(object)
+        }
+        Some("array") => {
+            let len = schema
+                .get("minItems")
+                .and_then(Value::as_u64)
+                .unwrap_or(1)
+                .max(1);
+            let array = match schema.get("items") {
+                // Tuples, e.g. `(u32, String)`
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .map(|item| sample_at(root, item, depth + 1))
+                    .collect(),
+                Some(item) => (0..len).map(|_| sample_at(root, item, depth + 1)).collect(),
+                None => Vec::new(),
+            };
+            Value::Array(array)
+        }
+        Some("string") => Value::String(sample_string(schema)),
+        Some("integer") => {
+            // Zero, unless out of the bounds
+            let (low, high) = integer_bounds(schema);
+            let value = low.map_or(0, |low| low.max(0));
+            Value::from(high.map_or(value, |high| high.min(value)))
+        }
+        Some("number") => {
+            let (low, high) = number_bounds(schema);
+            let value = low.map_or(0.0, |low| low.max(0.0));
+            Value::from(high.map_or(value, |high| high.min(value)))
+        }
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Returns the lowest and highest values allowed by an integer schema, if bounded.
+fn integer_bounds(schema: &Map<String, Value>) -> (Option<i64>, Option<i64>) {
+    let bound = |inclusive: &str, exclusive: &str, inwards: i64| {
+        schema
+            .get(inclusive)
+            .and_then(Value::as_i64)
+            .or_else(|| schema.get(exclusive)?.as_i64()?.checked_add(inwards))
+    };
+    (
+        bound("minimum", "exclusiveMinimum", 1),
+        bound("maximum", "exclusiveMaximum", -1),
+    )
+}
+
+/// Returns the lowest and highest values allowed by a number schema, if bounded. Exclusive bounds
+/// are moved inwards by one, or to the middle of the bounds when they're closer than that.
+fn number_bounds(schema: &Map<String, Value>) -> (Option<f64>, Option<f64>) {
+    let get = |key: &str| schema.get(key).and_then(Value::as_f64);
+    let low = get("minimum").or_else(|| get("exclusiveMinimum").map(|min| min + 1.0));
+    let high = get("maximum").or_else(|| get("exclusiveMaximum").map(|max| max - 1.0));
+    match (low, high) {
+        (Some(low), Some(high)) if low > high => {
+            let low = get("minimum")
+                .or_else(|| get("exclusiveMinimum"))
+                .unwrap_or(low);
+            let high = get("maximum")
+                .or_else(|| get("exclusiveMaximum"))
+                .unwrap_or(high);
+            let middle = low / 2.0 + high / 2.0;
+            (Some(middle), Some(middle))
+        }
+        bounds => bounds,
+    }
+}
+
+/// Returns the target of a local `$ref` (e.g. `#/definitions/User`) in the schema, if any.
+fn resolve_ref<'a>(root: &'a Value, schema: &Map<String, Value>) -> Option<&'a Value> {
+    let reference = schema.get("$ref")?.as_str()?;
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+/// Returns the value the schema suggests for itself, if any.
+fn suggested_value(schema: &Map<String, Value>) -> Option<&Value> {
+    schema
+        .get("const")
+        .or_else(|| schema.get("examples")?.as_array()?.first())
+        .or_else(|| schema.get("default"))
+        .or_else(|| schema.get("enum")?.as_array()?.first())
+}
+
+/// Returns the type of the schema, preferring any type over `null` when there are several.
+fn schema_type(schema: &Map<String, Value>) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(ty)) => Some(ty),
+        Some(Value::Array(types)) => {
+            let mut types = types.iter().filter_map(Value::as_str);
+            let first = types.clone().next();
+            types.find(|ty| *ty != "null").or(first)
+        }
+        _ if schema.contains_key("properties") => Some("object"),
+        _ if schema.contains_key("items") => Some("array"),
+        _ => None,
+    }
+}
+
+fn sample_string(schema: &Map<String, Value>) -> String {
+    let formatted = match schema.get("format").and_then(Value::as_str) {
+        Some("date-time") => "1970-01-01T00:00:00Z",
+        Some("date") => "1970-01-01",
+        Some("time") => "00:00:00",
+        Some("email") => "user@example.com",
+        Some("uri") => "https://example.com",
+        Some("uuid") => "00000000-0000-0000-0000-000000000000",
+        Some("ipv4") => "127.0.0.1",
+        Some("ipv6") => "::1",
+        _ => "string",
+    };
+
+    let min_length = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max_length = schema.get("maxLength").and_then(Value::as_u64);
+    match max_length {
+        Some(max_length) if (max_length as usize) < formatted.len() => {
+            "s".repeat(max_length as usize)
+        }
+        _ if formatted.len() < min_length => "s".repeat(min_length),
+        _ => formatted.to_string(),
+    }
+}
+
+#[cfg(feature = "test")]
+/// A small seeded pseudo-random generator (xorshift64*), so generated inputs can be reproduced.
+pub(crate) struct Rng(u64);
+
+#[cfg(feature = "test")]
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state of xorshift must never be zero
+        Rng(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in `0..len`, which must not be empty.
+    pub(crate) fn below(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// Returns `true` one time in `n`.
+    pub(crate) fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        match items.len() {
+            0 => None,
+            len => items.get(self.below(len)),
+        }
+    }
+}
+
+#[cfg(feature = "test")]
+/// Generates a random value that is expected to match the schema. As keywords such as `pattern`
+/// or `multipleOf` aren't followed, the value should still be checked against the schema.
+pub(crate) fn arbitrary(schema: &Value, rng: &mut Rng) -> Value {
+    arbitrary_at(schema, schema, rng, 0)
+}
+
+#[cfg(feature = "test")]
+fn arbitrary_at(root: &Value, schema: &Value, rng: &mut Rng, depth: usize) -> Value {
+    let Some(schema) = schema.as_object() else {
+        return Value::Null;
+    };
+    if depth > MAX_DEPTH {
+        return sample_at(root, &Value::Object(schema.clone()), depth);
+    }
+
+    if let Some(target) = resolve_ref(root, schema) {
+        return arbitrary_at(root, target, rng, depth + 1);
+    }
+    if let Some(value) = schema.get("const") {
+        return value.clone();
+    }
+    if let Some(value) = schema.get("enum").and_then(|e| rng.pick(e.as_array()?)) {
+        return value.clone();
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variant) = schema.get(key).and_then(|s| rng.pick(s.as_array()?)) {
+            return arbitrary_at(root, variant, rng, depth + 1);
+        }
+    }
+    if let Some(first) = schema.get("allOf").and_then(|s| s.as_array()?.first()) {
+        return arbitrary_at(root, first, rng, depth + 1);
+    }
+
+    let ty = match schema.get("type") {
+        Some(Value::Array(types)) => rng.pick(types).and_then(Value::as_str),
+        _ => schema_type(schema),
+    };
+    match ty {
+        Some("object") => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let mut object = Map::new();
+            for (name, property) in schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if required.contains(&name.as_str()) || !rng.one_in(3) {
+                    object.insert(name.clone(), arbitrary_at(root, property, rng, depth + 1));
+                }
+            }
+            // Maps, e.g. `HashMap<String, T>`
+            if let Some(values @ Value::Object(_)) = schema.get("additionalProperties")
+                && !schema.contains_key("properties")
+            {
+                for _ in 0..rng.below(3) {
+                    let key = arbitrary_string(&Map::new(), rng);
+                    object.insert(key, arbitrary_at(root, values, rng, depth + 1));
+                }
+            }
+            Value::Object(object)
+        }
+        Some("array") => {
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+            let max = schema
+                .get("maxItems")
+                .and_then(Value::as_u64)
+                .map_or(min + 4, |max| max as usize);
+            let array = match schema.get("items") {
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .map(|item| arbitrary_at(root, item, rng, depth + 1))
+                    .collect(),
+                Some(item) => (0..min + rng.below(max.saturating_sub(min) + 1))
+                    .map(|_| arbitrary_at(root, item, rng, depth + 1))
+                    .collect(),
+                None => Vec::new(),
+            };
+            Value::Array(array)
+        }
+        Some("string") => Value::String(arbitrary_string(schema, rng)),
+        Some("integer") => {
+            let (low, high) = match integer_bounds(schema) {
+                (Some(low), Some(high)) => (low, high.max(low)),
+                (Some(low), None) => (low, low.saturating_add(1000)),
+                (None, Some(high)) => (high.saturating_sub(1000), high),
+                (None, None) => (-1000, 1000),
+            };
+            // Favour the bounds and zero, where bugs tend to be
+            let value = match rng.below(8) {
+                0 => low,
+                1 => high,
+                2 => 0.clamp(low, high),
+                _ => {
+                    let span = (high as i128 - low as i128 + 1) as u128;
+                    (low as i128 + (rng.next_u64() as u128 % span) as i128) as i64
+                }
+            };
+            Value::from(value)
+        }
+        Some("number") => {
+            // The missing bound is derived from the other, so they're always in order
+            let (low, high) = match number_bounds(schema) {
+                (Some(low), Some(high)) => (low, high.max(low)),
+                (Some(low), None) => (low, low + 1000.0),
+                (None, Some(high)) => (high - 1000.0, high),
+                (None, None) => (-1000.0, 1000.0),
+            };
+            let fraction = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+            Value::from(low + fraction * (high - low))
+        }
+        Some("boolean") => Value::Bool(rng.one_in(2)),
+        Some("null") => Value::Null,
+        _ => sample_at(root, &Value::Object(schema.clone()), depth),
+    }
+}
+
+#[cfg(feature = "test")]
+fn arbitrary_string(schema: &Map<String, Value>, rng: &mut Rng) -> String {
+    if schema.contains_key("format") || schema.contains_key("pattern") {
+        return sample_string(schema);
+    }
+
+    const CHARS: &[char] = &[
+        'a', 'z', 'A', 'Z', '0', '9', ' ', '-', '_', '"', '\\', 'é', '☃', '🦀',
+    ];
+    let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max = schema
+        .get("maxLength")
+        .and_then(Value::as_u64)
+        .map_or(min + 12, |max| max as usize);
+    let len = min + rng.below(max.saturating_sub(min) + 1);
+    (0..len).map(|_| CHARS[rng.below(CHARS.len())]).collect()
+}
+
+#[cfg(feature = "test")]
+/// Generates values that don't match the schema, e.g. of another type or missing a required
+/// property, out of which a random one is returned.
+pub(crate) fn invalid(
+    schema: &Value,
+    validator: &jsonschema::Validator,
+    rng: &mut Rng,
+) -> Option<Value> {
+    // Structs also deserialize from arrays, which only a long array reliably rules out
+    let array = Value::Array(vec![Value::Bool(true); 32]);
+    let mut candidates = vec![
+        Value::Null,
+        Value::Bool(true),
+        Value::from(-1),
+        Value::from(4.5),
+        Value::from(""),
+        Value::from("not valid"),
+        array.clone(),
+        Value::Object(Map::new()),
+    ];
+
+    let object = schema.as_object();
+    for bound in ["minimum", "maximum"] {
+        if let Some(bound) = object.and_then(|s| s.get(bound)?.as_i64()) {
+            candidates.extend([bound.saturating_sub(1), bound.saturating_add(1)].map(Value::from));
+        }
+    }
+    // A valid value with one property missing or of the wrong type
+    if let Value::Object(valid) = arbitrary(schema, rng) {
+        for name in valid.keys() {
+            let mut missing = valid.clone();
+            missing.remove(name);
+            candidates.push(Value::Object(missing));
+
+            let mut wrong = valid.clone();
+            wrong.insert(name.clone(), array.clone());
+            candidates.push(Value::Object(wrong));
+        }
+    }
+
+    candidates.retain(|candidate| !validator.is_valid(candidate));
+    rng.pick(&candidates).cloned()
+}
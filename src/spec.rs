@@ -10,6 +10,48 @@ use typed_builder::TypedBuilder;
 
 type Schema = schemars::Schema;
 
+/// Names of the specification extensions (`x-` fields) this crate produces.
+pub mod extension {
+    /// Name of the method that replaces a deprecated method.
+    pub const REPLACED_BY: &str = "x-replaced-by";
+    /// Scopes all required to call a method.
+    pub const SECURITY: &str = "x-security";
+    /// Number of calls allowed per period, e.g. `100/s` (see [`crate::RateLimit`]).
+    pub const RATE_LIMIT: &str = "x-rate-limit";
+    /// Number of calls handled at the same time.
+    pub const MAX_CONCURRENCY: &str = "x-max-concurrency";
+    /// Longest an async method is given to respond, e.g. `5s` (see [`crate::parse_duration`]).
+    pub const TIMEOUT: &str = "x-timeout";
+    /// Names of the notifications and of the unsubscribe method of a method returning an
+    /// [`crate::RpcStream`].
+    pub const SUBSCRIPTION: &str = "x-subscription";
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    /// Returns true if the key is the name of a specification extension, i.e. starts with `x-`.
+    fn is_extension(key: &str) -> bool {
+        key.starts_with("x-")
+    }
+
+    /// Writes the extensions, skipping the keys that aren't, so they can't overwrite a field.
+    pub(super) fn serialize<S: Serializer>(
+        extensions: &HashMap<String, serde_json::Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(extensions.iter().filter(|(key, _)| is_extension(key)))
+    }
+
+    /// Reads the extensions out of the fields left, ignoring the unknown ones that aren't.
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<String, serde_json::Value>, D::Error> {
+        let mut extensions = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        extensions.retain(|key, _| is_extension(key));
+        Ok(extensions)
+    }
+}
+
 /// The root object of the OpenRPC document semver **1.3.2**
 ///
 /// The contents of this object represent a whole OpenRPC document.
@@ -175,8 +217,9 @@ pub struct Method {
     /// REQUIRED. The canonical name for the method. Must be unique.
     pub name: String,
     /// A list of tags for API documentation control.
+    /// Tags declared in [`Components::tags`] are referenced rather than inlined.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tags: Option<Vec<Tag>>,
+    pub tags: Option<Vec<ReferenceOr<Tag>>>,
     /// A short summary of what the method does.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
@@ -209,6 +252,48 @@ pub struct Method {
     /// Example params-to-result pairings.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub examples: Option<Vec<ExamplePairing>>,
+    /// Specification extensions, i.e. fields starting with `x-` (see [`extension`]). Other keys
+    /// are skipped when written.
+    #[serde(flatten, with = "extension")]
+    #[builder(default)]
+    pub extensions: HashMap<String, serde_json::Value>,
+}
+
+impl Method {
+    /// Returns true if the method is only used as a notification, i.e. it has no result.
+    pub fn is_notification(&self) -> bool {
+        self.result.is_none()
+    }
+
+    /// Returns the name of the method replacing this (deprecated) method, if any.
+    pub fn replaced_by(&self) -> Option<&str> {
+        self.extensions
+            .get(extension::REPLACED_BY)
+            .and_then(|value| value.as_str())
+    }
+
+    /// Returns the scopes required to call this method, if any.
+    pub fn required_scopes(&self) -> Vec<&str> {
+        self.extensions
+            .get(extension::SECURITY)
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|scope| scope.as_str())
+            .collect()
+    }
+
+    /// Returns the names of the tags on this method, whether inlined or referenced.
+    pub fn tag_names(&self) -> Vec<&str> {
+        self.tags
+            .iter()
+            .flatten()
+            .filter_map(|tag| match tag {
+                ReferenceOr::Item(tag) => Some(tag.name.as_str()),
+                ReferenceOr::Reference(reference) => reference.tag_name(),
+            })
+            .collect()
+    }
 }
 
 /// Describes content for parameters or results. Must have a schema.
@@ -287,6 +372,7 @@ pub struct Error {
 
 /// Holds a set of reusable objects.
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option)))]
 #[serde(rename_all = "camelCase")]
 pub struct Components {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -307,8 +393,10 @@ pub struct Components {
 
 /// Metadata for a tag.
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option)))]
 #[serde(rename_all = "camelCase")]
 pub struct Tag {
+    #[builder(setter(!strip_option))]
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
@@ -318,10 +406,44 @@ pub struct Tag {
     pub external_docs: Option<ExternalDocumentation>,
 }
 
+/// A simple object to allow referencing other components in the specification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypedBuilder)]
+pub struct Reference {
+    /// REQUIRED. The reference string, e.g. `#/components/tags/users`.
+    #[serde(rename = "$ref")]
+    pub reference: String,
+}
+
+impl Reference {
+    const TAG_PREFIX: &str = "#/components/tags/";
+
+    /// Creates a reference to a tag declared in [`Components::tags`].
+    pub fn tag(name: &str) -> Self {
+        Reference {
+            reference: format!("{}{name}", Self::TAG_PREFIX),
+        }
+    }
+
+    /// Returns the tag name if this is a reference into [`Components::tags`].
+    pub fn tag_name(&self) -> Option<&str> {
+        self.reference.strip_prefix(Self::TAG_PREFIX)
+    }
+}
+
+/// Either an inline object or a [`Reference`] to one held in [`Components`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReferenceOr<T> {
+    Reference(Reference),
+    Item(T),
+}
+
 /// Allows referencing an external resource for extended documentation.
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option)))]
 pub struct ExternalDocumentation {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[builder(setter(!strip_option))]
     pub url: String,
 }
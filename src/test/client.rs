@@ -0,0 +1,324 @@
+use crate::{BoxedRpcService, NotificationLayer, SpecModule};
+use jsonrpsee::{
+    Methods,
+    core::{
+        ClientError,
+        server::{BatchResponseBuilder, ConnectionId, MethodCallback},
+    },
+    server::middleware::rpc::{
+        Batch, BatchEntry, MethodResponse, Notification, RpcServiceBuilder, RpcServiceT,
+    },
+    types::{
+        ErrorCode, ErrorObject, ErrorObjectOwned, Id, Params, Request, Response, ResponsePayload,
+        error::INTERNAL_ERROR_CODE,
+    },
+};
+use serde_json::value::RawValue;
+use std::{
+    borrow::Cow,
+    fmt,
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Creates a client calling the methods of the provided `SpecModule` in-process, without
+/// starting a server or binding a port, which keeps large test suites fast.
+///
+/// The calls, batched or not, go through the same RPC middleware as with [`test_server`](super::test_server):
+/// the [`NotificationLayer`] and the layer of [`SpecModule::rpc_middleware`]. As no server is
+/// involved, subscriptions aren't supported.
+///
+/// # Type Parameters
+/// - `Context`: The context type that must implement `Send` and `Sync` traits and have a static lifetime.
+///
+/// # Arguments
+/// - `module`: The `SpecModule` to be tested, which will be converted into a JSON-RPC module.
+///
+/// # Example
+/// ```
+/// use openspec_jsonrpsee::{SpecModule, rpc, test_in_process};
+///
+/// #[rpc]
+/// fn greet(name: String) -> String {
+///     format!("Hello {name}")
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), jsonrpsee::core::ClientError> {
+///     let mut module = SpecModule::new(());
+///     module.add_method(Greet)?;
+///
+///     let client = test_in_process(module);
+///     assert_eq!(Greet::request(&client, "Jane".into()).await?, "Hello Jane");
+///     Ok(())
+/// }
+/// ```
+pub fn test_in_process<Context: Send + Sync + 'static>(
+    module: SpecModule<Context>,
+) -> InProcessClient {
+    let spec_layer = module.rpc_middleware();
+    let service = RpcServiceBuilder::new()
+        .layer(NotificationLayer)
+        .layer(spec_layer)
+        .service(MethodsService(module.into_jsonrpsee_module().into()));
+    InProcessClient::with_service(BoxedRpcService::new(service))
+}
+
+/// A JSON-RPC client dispatching its calls straight into the methods of an `RpcModule`.
+#[derive(Clone)]
+pub struct InProcessClient {
+    service: BoxedRpcService,
+    next_id: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for InProcessClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InProcessClient").finish_non_exhaustive()
+    }
+}
+
+impl InProcessClient {
+    /// Creates a client for the given methods, e.g. of a `jsonrpsee::RpcModule`, with only the
+    /// [`NotificationLayer`] as RPC middleware.
+    pub fn new(methods: impl Into<Methods>) -> Self {
+        let service = RpcServiceBuilder::new()
+            .layer(NotificationLayer)
+            .service(MethodsService(methods.into()));
+        InProcessClient::with_service(BoxedRpcService::new(service))
+    }
+
+    fn with_service(service: BoxedRpcService) -> Self {
+        InProcessClient {
+            service,
+            next_id: Default::default(),
+        }
+    }
+
+    /// Calls the method, returning its result or the error object it responded with.
+    ///
+    /// Notifications are never responded to, so their result is `null`.
+    pub(super) async fn call(
+        &self,
+        method: &str,
+        params: Option<Box<RawValue>>,
+        notification: bool,
+    ) -> Result<Result<Box<RawValue>, ErrorObjectOwned>, ClientError> {
+        if notification {
+            let notification = Notification::new(method.into(), params.map(Cow::Owned));
+            self.service.notification(notification).await;
+            return Ok(Ok(RawValue::NULL.to_owned()));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = Request::owned(method.to_string(), params, Id::Number(id));
+        let response = self.service.call(request).await;
+        let response: Response<Box<RawValue>> = serde_json::from_str(response.as_json().get())?;
+        match response.payload {
+            ResponsePayload::Success(result) => Ok(Ok(result.into_owned())),
+            ResponsePayload::Error(error) => Ok(Err(error.into_owned())),
+        }
+    }
+
+    /// Calls the methods in a single batch, returning their results or the error objects they
+    /// responded with, in the order of the calls.
+    pub(super) async fn call_batch(
+        &self,
+        calls: Vec<(&str, Option<Box<RawValue>>)>,
+    ) -> Result<Vec<Result<Box<RawValue>, ErrorObjectOwned>>, ClientError> {
+        let first_id = self
+            .next_id
+            .fetch_add(calls.len() as u64, Ordering::Relaxed);
+        let mut batch = Batch::with_capacity(calls.len());
+        for (id, (method, params)) in (first_id..).zip(calls) {
+            batch.push(Request::owned(method.to_string(), params, Id::Number(id)));
+        }
+        let len = batch.len();
+
+        let response = self.service.batch(batch).await;
+        let json = response.as_json().get();
+        // A batch rejected as a whole, e.g. for its response being too big, gets a single error
+        let responses: Vec<Response<Box<RawValue>>> = match serde_json::from_str(json) {
+            Ok(responses) => responses,
+            Err(_) => {
+                let response: Response<Box<RawValue>> = serde_json::from_str(json)?;
+                return Err(match response.payload {
+                    ResponsePayload::Error(error) => ClientError::Call(error.into_owned()),
+                    ResponsePayload::Success(_) => {
+                        ClientError::Custom("a batch was responded to with a single result".into())
+                    }
+                });
+            }
+        };
+
+        // The responses are put back in the order of the calls
+        let mut results = vec![None; len];
+        for response in responses {
+            let index = match response.id {
+                Id::Number(id) if id >= first_id && id - first_id < len as u64 => {
+                    (id - first_id) as usize
+                }
+                id => return Err(ClientError::Custom(format!("unexpected response id {id}"))),
+            };
+            results[index] = Some(match response.payload {
+                ResponsePayload::Success(result) => Ok(result.into_owned()),
+                ResponsePayload::Error(error) => Err(error.into_owned()),
+            });
+        }
+        results
+            .into_iter()
+            .map(|result| {
+                result.ok_or_else(|| {
+                    ClientError::Custom("a call of the batch wasn't responded to".into())
+                })
+            })
+            .collect()
+    }
+}
+
+/// The innermost RPC service of an [`InProcessClient`], calling the methods like the service of
+/// a server does.
+#[derive(Clone)]
+struct MethodsService(Methods);
+
+impl RpcServiceT for MethodsService {
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let Request {
+            id,
+            method,
+            params,
+            extensions,
+            ..
+        } = request;
+        let callback = self
+            .0
+            .method_with_name(&method)
+            .map(|(_, callback)| callback.clone());
+
+        async move {
+            let params = Params::new(params.as_ref().map(|params| params.get()));
+            match callback {
+                Some(MethodCallback::Sync(callback)) => {
+                    callback(id, params, usize::MAX, extensions)
+                }
+                Some(MethodCallback::Async(callback)) => {
+                    let (id, params) = (id.into_owned(), params.into_owned());
+                    callback(id, params, ConnectionId(0), usize::MAX, extensions).await
+                }
+                Some(MethodCallback::Subscription(_) | MethodCallback::Unsubscription(_)) => {
+                    let error = ErrorObject::borrowed(
+                        INTERNAL_ERROR_CODE,
+                        "Subscriptions aren't supported in-process",
+                        None,
+                    );
+                    MethodResponse::error(id, error)
+                }
+                None => MethodResponse::error(id, ErrorObject::from(ErrorCode::MethodNotFound)),
+            }
+        }
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let service = self.clone();
+
+        async move {
+            let mut responses = BatchResponseBuilder::new_with_limit(usize::MAX);
+            for entry in batch {
+                let response = match entry {
+                    Ok(BatchEntry::Call(request)) => service.call(request).await,
+                    Ok(BatchEntry::Notification(_)) => continue,
+                    Err(error) => {
+                        let (error, id) = error.into_parts();
+                        MethodResponse::error(id, error)
+                    }
+                };
+                if let Err(response) = responses.append(response) {
+                    return response;
+                }
+            }
+            MethodResponse::from_batch(responses.finish())
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        _notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        // Like the service of a server, notifications are left to the `NotificationLayer`
+        std::future::ready(MethodResponse::notification())
+    }
+}
+
+/// Implements `ClientT` for a client with a private method calling a single JSON-RPC method:
+/// `async fn call(&self, method: &str, params: Option<Box<RawValue>>, notification: bool)
+/// -> Result<Result<Box<RawValue>, ErrorObjectOwned>, ClientError>`, and one calling a batch:
+/// `async fn call_batch(&self, calls: Vec<(&str, Option<Box<RawValue>>)>)
+/// -> Result<Vec<Result<Box<RawValue>, ErrorObjectOwned>>, ClientError>`.
+macro_rules! impl_client_t {
+    ($client:ty) => {
+        impl ::jsonrpsee::core::client::ClientT for $client {
+            async fn notification<Params>(
+                &self,
+                method: &str,
+                params: Params,
+            ) -> ::std::result::Result<(), ::jsonrpsee::core::ClientError>
+            where
+                Params: ::jsonrpsee::core::traits::ToRpcParams + Send,
+            {
+                self.call(method, params.to_rpc_params()?, true)
+                    .await
+                    .map(|_| ())
+            }
+
+            async fn request<R, Params>(
+                &self,
+                method: &str,
+                params: Params,
+            ) -> ::std::result::Result<R, ::jsonrpsee::core::ClientError>
+            where
+                R: ::serde::de::DeserializeOwned,
+                Params: ::jsonrpsee::core::traits::ToRpcParams + Send,
+            {
+                let result = self.call(method, params.to_rpc_params()?, false).await??;
+                Ok(serde_json::from_str(result.get())?)
+            }
+
+            async fn batch_request<'a, R>(
+                &self,
+                batch: ::jsonrpsee::core::params::BatchRequestBuilder<'a>,
+            ) -> ::std::result::Result<
+                ::jsonrpsee::core::client::BatchResponse<'a, R>,
+                ::jsonrpsee::core::ClientError,
+            >
+            where
+                R: ::serde::de::DeserializeOwned + ::std::fmt::Debug + 'a,
+            {
+                // Responses are decoded once all calls are made, as `R` isn't required to be `Send`
+                let entries = self
+                    .call_batch(batch.build()?)
+                    .await?
+                    .into_iter()
+                    .map(|response| match response {
+                        Ok(result) => Ok(Ok(serde_json::from_str(result.get())?)),
+                        Err(error) => Ok(Err(error)),
+                    })
+                    .collect::<::std::result::Result<Vec<_>, ::jsonrpsee::core::ClientError>>()?;
+
+                let successful = entries.iter().filter(|entry| entry.is_ok()).count();
+                let failed = entries.len() - successful;
+                Ok(::jsonrpsee::core::client::BatchResponse::new(
+                    successful, entries, failed,
+                ))
+            }
+        }
+    };
+}
+pub(super) use impl_client_t;
+
+impl_client_t!(InProcessClient);
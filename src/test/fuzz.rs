@@ -0,0 +1,334 @@
+use crate::{SpecModule, schema, spec};
+use jsonrpsee::{
+    Methods,
+    types::{
+        ErrorObjectOwned,
+        error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+    },
+};
+use serde_json::Value;
+use std::fmt;
+use typed_builder::TypedBuilder;
+
+/// Options of [`fuzz_module`].
+#[derive(Debug, Clone, TypedBuilder)]
+pub struct FuzzOptions {
+    /// How many times every method is called with valid params, and again with invalid params.
+    #[builder(default = 100)]
+    pub iterations: usize,
+    /// Seed of the generated params, so a failing run can be reproduced.
+    #[builder(default = 0x5EED)]
+    pub seed: u64,
+}
+
+impl Default for FuzzOptions {
+    fn default() -> Self {
+        FuzzOptions::builder().build()
+    }
+}
+
+/// The outcome of [`fuzz_module`].
+#[derive(Debug, Default)]
+pub struct FuzzReport {
+    /// How many calls were made in total.
+    pub calls: usize,
+    /// Every call that didn't behave as its specification describes.
+    pub failures: Vec<FuzzFailure>,
+}
+
+impl FuzzReport {
+    /// Panics listing every failure, if any.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.failures.is_empty() {
+            let failures: Vec<String> = self.failures.iter().map(ToString::to_string).collect();
+            panic!(
+                "{} of {} calls failed:\n{}",
+                self.failures.len(),
+                self.calls,
+                failures.join("\n")
+            );
+        }
+    }
+}
+
+/// A call made by [`fuzz_module`] that didn't behave as its specification describes.
+#[derive(Debug)]
+pub struct FuzzFailure {
+    /// The name of the method called.
+    pub method: String,
+    /// The params of the call, if any.
+    pub params: Option<Value>,
+    /// What went wrong.
+    pub kind: FuzzFailureKind,
+}
+
+/// What went wrong in a [`FuzzFailure`].
+#[derive(Debug)]
+pub enum FuzzFailureKind {
+    /// The schema of a param or of the result isn't a valid JSON schema.
+    InvalidSchema(String),
+    /// The method panicked.
+    Panic(String),
+    /// Valid params produced an internal error (`-32603`).
+    InternalError(ErrorObjectOwned),
+    /// Valid params were rejected as invalid (`-32602`), i.e. the schema accepts more than the method.
+    ValidParamsRejected(ErrorObjectOwned),
+    /// Invalid params produced something other than an invalid params error (`-32602`).
+    InvalidParamsAccepted(Value),
+    /// The result doesn't match the result schema.
+    InvalidResult { result: Value, errors: Vec<String> },
+    /// The response has an `error` that isn't a JSON-RPC error object.
+    MalformedError { error: Value, message: String },
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`", self.method)?;
+        if let Some(params) = &self.params {
+            write!(f, " with params {params}")?;
+        }
+        match &self.kind {
+            FuzzFailureKind::InvalidSchema(e) => write!(f, ": invalid schema: {e}"),
+            FuzzFailureKind::Panic(message) => write!(f, ": panicked: {message}"),
+            FuzzFailureKind::InternalError(e) => write!(f, ": internal error: {e}"),
+            FuzzFailureKind::ValidParamsRejected(e) => write!(f, ": valid params rejected: {e}"),
+            FuzzFailureKind::InvalidParamsAccepted(response) => {
+                write!(f, ": invalid params not rejected: {response}")
+            }
+            FuzzFailureKind::InvalidResult { result, errors } => {
+                write!(f, ": invalid result {result}: {}", errors.join(", "))
+            }
+            FuzzFailureKind::MalformedError { error, message } => {
+                write!(f, ": malformed error {error}: {message}")
+            }
+        }
+    }
+}
+
+/// Calls every method of the `SpecModule` many times, with params generated from the param schemas,
+/// checking that the methods behave as their specification describes:
+/// - Valid params never produce a panic or an internal error, and the result matches the result schema.
+/// - Invalid params (e.g. of the wrong type or missing) always produce an invalid params error (`-32602`).
+/// - Errors are JSON-RPC error objects.
+///
+/// The methods are called in-process, without a server.
+///
+/// # Arguments
+/// - `module`: The `SpecModule` to be tested.
+/// - `options`: The number of iterations and the seed of the generated params.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{FuzzOptions, SpecModule, fuzz_module};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let module = SpecModule::new(());
+///     fuzz_module(module, FuzzOptions::default()).await.assert_ok();
+/// }
+/// ```
+pub async fn fuzz_module<Context: Send + Sync + 'static>(
+    module: SpecModule<Context>,
+    options: FuzzOptions,
+) -> FuzzReport {
+    let spec_methods = module.spec().methods.clone();
+    let methods: Methods = module.into_jsonrpsee_module().into();
+    let mut rng = schema::Rng::new(options.seed);
+    let mut report = FuzzReport::default();
+
+    for method in &spec_methods {
+        let mut fuzzer = match MethodFuzzer::new(method, methods.clone()) {
+            Ok(fuzzer) => fuzzer,
+            Err(kind) => {
+                report.failures.push(FuzzFailure {
+                    method: method.name.clone(),
+                    params: None,
+                    kind,
+                });
+                continue;
+            }
+        };
+        for _ in 0..options.iterations {
+            fuzzer.call_valid(&mut rng, &mut report).await;
+            fuzzer.call_invalid(&mut rng, &mut report).await;
+        }
+    }
+
+    report
+}
+
+/// Calls one method, with the validators of its schemas.
+struct MethodFuzzer<'a> {
+    method: &'a spec::Method,
+    methods: Methods,
+    params: Vec<(Value, bool, jsonschema::Validator)>,
+    result: Option<jsonschema::Validator>,
+    id: u64,
+}
+
+impl<'a> MethodFuzzer<'a> {
+    fn new(method: &'a spec::Method, methods: Methods) -> Result<Self, FuzzFailureKind> {
+        let params = method
+            .params
+            .iter()
+            .map(|param| {
+                let schema = param.schema.as_value().clone();
+                let validator =
+                    schema::validator(&schema).map_err(FuzzFailureKind::InvalidSchema)?;
+                Ok((schema, param.required.unwrap_or_default(), validator))
+            })
+            .collect::<Result<_, _>>()?;
+        let result = method
+            .result
+            .as_ref()
+            .map(|result| schema::validator(result.schema.as_value()))
+            .transpose()
+            .map_err(FuzzFailureKind::InvalidSchema)?;
+
+        Ok(MethodFuzzer {
+            method,
+            methods,
+            params,
+            result,
+            id: 0,
+        })
+    }
+
+    async fn call_valid(&mut self, rng: &mut schema::Rng, report: &mut FuzzReport) {
+        let values: Vec<Value> = self
+            .params
+            .iter()
+            .map(|(schema, _, _)| schema::arbitrary(schema, rng))
+            .collect();
+        // Values the generator couldn't make valid (e.g. for a `pattern`) prove nothing
+        let all_valid = self
+            .params
+            .iter()
+            .zip(&values)
+            .all(|((_, _, validator), value)| validator.is_valid(value));
+        if !all_valid {
+            return;
+        }
+
+        let params = (!values.is_empty()).then_some(Value::Array(values));
+        let kind = match self.call(params.clone(), report).await {
+            Err(kind) => Some(kind),
+            Ok(Ok(result)) => self.result.as_ref().and_then(|validator| {
+                let errors = schema::errors(validator, &result);
+                (!errors.is_empty()).then_some(FuzzFailureKind::InvalidResult { result, errors })
+            }),
+            Ok(Err(error)) if error.code() == INVALID_PARAMS_CODE => {
+                Some(FuzzFailureKind::ValidParamsRejected(error))
+            }
+            Ok(Err(error)) if error.code() == INTERNAL_ERROR_CODE => {
+                Some(FuzzFailureKind::InternalError(error))
+            }
+            // Any other error is up to the method
+            Ok(Err(_)) => None,
+        };
+        self.push_failure(report, params, kind);
+    }
+
+    async fn call_invalid(&mut self, rng: &mut schema::Rng, report: &mut FuzzReport) {
+        if self.params.is_empty() {
+            return;
+        }
+
+        let index = rng.below(self.params.len());
+        let mut values: Vec<Value> = self
+            .params
+            .iter()
+            .map(|(schema, _, _)| schema::arbitrary(schema, rng))
+            .collect();
+        let (schema, required, validator) = &self.params[index];
+        let missing = *required && !validator.is_valid(&Value::Null);
+        if missing && rng.one_in(4) {
+            values.truncate(index);
+        } else if let Some(invalid) = schema::invalid(schema, validator, rng) {
+            values[index] = invalid;
+        } else {
+            // Anything is valid for this param
+            return;
+        }
+
+        let params = Some(Value::Array(values));
+        let kind = match self.call(params.clone(), report).await {
+            Err(kind) => Some(kind),
+            Ok(Err(error)) if error.code() == INVALID_PARAMS_CODE => None,
+            Ok(Ok(result)) => Some(FuzzFailureKind::InvalidParamsAccepted(result)),
+            Ok(Err(error)) => Some(FuzzFailureKind::InvalidParamsAccepted(
+                serde_json::to_value(error).unwrap_or_default(),
+            )),
+        };
+        self.push_failure(report, params, kind);
+    }
+
+    /// Calls the method in its own task, so a panic is caught and reported.
+    async fn call(
+        &mut self,
+        params: Option<Value>,
+        report: &mut FuzzReport,
+    ) -> Result<Result<Value, ErrorObjectOwned>, FuzzFailureKind> {
+        self.id += 1;
+        report.calls += 1;
+
+        let mut request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "method": self.method.name,
+        });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+
+        let methods = self.methods.clone();
+        let call = tokio::spawn(async move {
+            let (response, _) = methods
+                .raw_json_request(&request.to_string(), 1)
+                .await
+                .expect("request should be valid JSON");
+            serde_json::from_str::<Value>(response.get()).expect("response should be valid JSON")
+        });
+        let mut response = match call.await {
+            Ok(response) => response,
+            Err(e) => {
+                let message = match e.try_into_panic() {
+                    Ok(panic) => panic
+                        .downcast_ref::<&str>()
+                        .map(ToString::to_string)
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_default(),
+                    Err(e) => e.to_string(),
+                };
+                return Err(FuzzFailureKind::Panic(message));
+            }
+        };
+
+        match response.get_mut("error").map(Value::take) {
+            Some(error) => match serde_json::from_value(error.clone()) {
+                Ok(error) => Ok(Err(error)),
+                Err(e) => Err(FuzzFailureKind::MalformedError {
+                    error,
+                    message: e.to_string(),
+                }),
+            },
+            None => Ok(Ok(response["result"].take())),
+        }
+    }
+
+    fn push_failure(
+        &self,
+        report: &mut FuzzReport,
+        params: Option<Value>,
+        kind: Option<FuzzFailureKind>,
+    ) {
+        if let Some(kind) = kind {
+            report.failures.push(FuzzFailure {
+                method: self.method.name.clone(),
+                params,
+                kind,
+            });
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! This module provides utilities for testing an `SpecModule` with a JSON-RPC server.
+//! It includes functions to set up a test server and an HTTP or WebSocket client for integration
+//! testing, an in-process client that needs no server at all, a client replaying recorded calls,
+//! and a harness calling every method with params generated from its specification.
+//! Specifications can be compared against committed snapshots with
+//! [`assert_spec_snapshot!`](crate::assert_spec_snapshot), and checked for problems with
+//! [`assert_spec_valid`].
+mod client;
+mod fuzz;
+mod replay;
+mod server;
+mod snapshot;
+mod validation;
+
+pub use client::*;
+pub use fuzz::*;
+pub use replay::*;
+pub use server::*;
+pub use snapshot::*;
+pub use validation::*;
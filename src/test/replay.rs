@@ -0,0 +1,322 @@
+//! This module provides a client that records the calls made to a `SpecModule` into a fixture
+//! file, and replays them offline, so client-side tests run without the backend (and its context).
+use super::{
+    InProcessClient, RecordedCall, SNAPSHOT_UPDATE_VAR, client::impl_client_t, test_in_process,
+};
+use crate::{OpenRpcSpec, SpecModule, schema::ParamsValidator};
+use jsonrpsee::{
+    core::ClientError,
+    types::{
+        ErrorObject, ErrorObjectOwned,
+        error::{
+            INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, METHOD_NOT_FOUND_CODE, METHOD_NOT_FOUND_MSG,
+        },
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json, value::RawValue};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// The content of a fixture file of a [`ReplayClient`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFixture {
+    /// The specification of the recorded module, which replayed params are checked against.
+    pub spec: OpenRpcSpec,
+    /// The recorded calls, in the order they were made.
+    pub calls: Vec<RecordedCall>,
+}
+
+/// A JSON-RPC client that either records the calls made to a `SpecModule` (see
+/// [`ReplayClient::record`]) or replays the responses of a fixture file (see [`ReplayClient::replay`]).
+///
+/// When replaying, the params of every call are validated against the param schemas of the
+/// recorded specification, so calls the backend would reject fail just the same, even offline.
+/// Repeated calls with the same params replay their recorded responses in order, e.g. the
+/// successive results of a stateful method.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{ReplayClient, SpecModule, rpc};
+///
+/// #[rpc]
+/// fn greet(name: String) -> String {
+///     format!("Hello {name}")
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Only creates the module when recording, i.e. the fixture is missing or outdated
+///     let client = ReplayClient::record_or_replay("tests/fixtures/greet.json", || {
+///         let mut module = SpecModule::new(());
+///         module.add_method(Greet).expect("method should register");
+///         module
+///     })?;
+///
+///     assert_eq!(Greet::request(&client, "Jane".into()).await?, "Hello Jane");
+///     client.save()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ReplayClient {
+    mode: ReplayMode,
+}
+
+#[derive(Clone)]
+enum ReplayMode {
+    Record {
+        client: InProcessClient,
+        fixture: Arc<Mutex<ReplayFixture>>,
+        path: PathBuf,
+    },
+    Replay {
+        methods: Arc<HashMap<String, ParamsValidator>>,
+        calls: Arc<Vec<RecordedCall>>,
+        /// Whether each recorded call was replayed, repeated calls replaying the next response
+        replayed: Arc<Mutex<Vec<bool>>>,
+    },
+}
+
+impl ReplayClient {
+    /// Creates a client calling the `SpecModule` in-process and recording every call, to be
+    /// written to the fixture file at `path` with [`ReplayClient::save`].
+    pub fn record<Context: Send + Sync + 'static>(
+        module: SpecModule<Context>,
+        path: impl Into<PathBuf>,
+    ) -> Self {
+        let fixture = ReplayFixture {
+            spec: module.spec().clone(),
+            calls: Vec::new(),
+        };
+        ReplayClient {
+            mode: ReplayMode::Record {
+                client: test_in_process(module),
+                fixture: Arc::new(Mutex::new(fixture)),
+                path: path.into(),
+            },
+        }
+    }
+
+    /// Creates a client replaying the calls of the fixture file at `path`.
+    ///
+    /// # Returns
+    /// - `Ok(ReplayClient)`: The client replaying the fixture.
+    /// - `Err(std::io::Error)`: An error if the fixture can't be read, or isn't a valid fixture.
+    pub fn replay(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let fixture: ReplayFixture = serde_json::from_str(&json)?;
+        Self::from_fixture(fixture)
+    }
+
+    /// Creates a client replaying the calls of the fixture.
+    ///
+    /// # Returns
+    /// - `Ok(ReplayClient)`: The client replaying the fixture.
+    /// - `Err(std::io::Error)`: An error if a param schema of the specification is invalid.
+    pub fn from_fixture(fixture: ReplayFixture) -> std::io::Result<Self> {
+        let methods = fixture
+            .spec
+            .methods
+            .iter()
+            .map(|method| {
+                let validator = ParamsValidator::new(method).map_err(|(param, message)| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "invalid schema for `{param}` of `{}`: {message}",
+                            method.name
+                        ),
+                    )
+                })?;
+                Ok((method.name.clone(), validator))
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(ReplayClient {
+            mode: ReplayMode::Replay {
+                methods: Arc::new(methods),
+                replayed: Arc::new(Mutex::new(vec![false; fixture.calls.len()])),
+                calls: Arc::new(fixture.calls),
+            },
+        })
+    }
+
+    /// Records the `SpecModule` created by `module` when the fixture file at `path` is missing or
+    /// the `OPENSPEC_UPDATE=1` environment variable is set, and replays the fixture otherwise.
+    pub fn record_or_replay<Context: Send + Sync + 'static>(
+        path: impl Into<PathBuf>,
+        module: impl FnOnce() -> SpecModule<Context>,
+    ) -> std::io::Result<Self> {
+        let path = path.into();
+        let update = std::env::var(SNAPSHOT_UPDATE_VAR).is_ok_and(|value| value == "1");
+        if update || !path.exists() {
+            Ok(Self::record(module(), path))
+        } else {
+            Self::replay(path)
+        }
+    }
+
+    /// Returns whether the client is recording, rather than replaying.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, ReplayMode::Record { .. })
+    }
+
+    /// Writes the recorded calls to the fixture file, when recording.
+    pub fn save(&self) -> std::io::Result<()> {
+        let ReplayMode::Record { fixture, path, .. } = &self.mode else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let fixture = fixture.lock().unwrap_or_else(|e| e.into_inner());
+        let json = serde_json::to_string_pretty(&*fixture)?;
+        std::fs::write(path, json + "\n")
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        params: Option<Box<RawValue>>,
+        notification: bool,
+    ) -> Result<Result<Box<RawValue>, ErrorObjectOwned>, ClientError> {
+        let params_value: Option<Value> = params
+            .as_ref()
+            .map(|params| serde_json::from_str(params.get()))
+            .transpose()?;
+
+        match &self.mode {
+            ReplayMode::Record {
+                client, fixture, ..
+            } => {
+                let response = client.call(method, params, notification).await?;
+                record_call(fixture, method, params_value, notification, &response);
+                Ok(response)
+            }
+            ReplayMode::Replay {
+                methods,
+                calls,
+                replayed,
+            } => {
+                let Some(validator) = methods.get(method) else {
+                    return Ok(Err(ErrorObject::owned(
+                        METHOD_NOT_FOUND_CODE,
+                        METHOD_NOT_FOUND_MSG,
+                        None::<()>,
+                    )));
+                };
+                if let Err(errors) = validator.validate(params_value.clone().unwrap_or_default()) {
+                    return Ok(Err(ErrorObject::owned(
+                        INVALID_PARAMS_CODE,
+                        INVALID_PARAMS_MSG,
+                        Some(errors),
+                    )));
+                }
+                if notification {
+                    return Ok(Ok(RawValue::NULL.to_owned()));
+                }
+
+                // The responses to a same call are replayed in the order they were recorded, e.g.
+                // the successive results of a stateful method
+                let mut replayed = replayed.lock().unwrap_or_else(|e| e.into_inner());
+                let recorded: Vec<usize> = (0..calls.len())
+                    .filter(|index| {
+                        let call = &calls[*index];
+                        call.request["method"] == method
+                            && call.request.get("params") == params_value.as_ref()
+                            && call.response.is_some()
+                    })
+                    .collect();
+                let Some(&index) = recorded.iter().find(|index| !replayed[**index]) else {
+                    let params = params_value.unwrap_or_default();
+                    return Err(ClientError::Custom(match recorded.len() {
+                        0 => format!("no recorded response for `{method}` with params {params}"),
+                        count => format!(
+                            "the {count} recorded responses for `{method}` with params {params} were all replayed"
+                        ),
+                    }));
+                };
+                replayed[index] = true;
+                let response = calls[index]
+                    .response
+                    .as_ref()
+                    .expect("recorded calls have a response");
+                match response.get("error") {
+                    Some(error) => Ok(Err(serde_json::from_value(error.clone())?)),
+                    None => Ok(Ok(serde_json::value::to_raw_value(&response["result"])?)),
+                }
+            }
+        }
+    }
+
+    async fn call_batch(
+        &self,
+        calls: Vec<(&str, Option<Box<RawValue>>)>,
+    ) -> Result<Vec<Result<Box<RawValue>, ErrorObjectOwned>>, ClientError> {
+        match &self.mode {
+            ReplayMode::Record {
+                client, fixture, ..
+            } => {
+                let requests = calls
+                    .iter()
+                    .map(|(method, params)| {
+                        let params_value: Option<Value> = params
+                            .as_ref()
+                            .map(|params| serde_json::from_str(params.get()))
+                            .transpose()?;
+                        Ok((method.to_string(), params_value))
+                    })
+                    .collect::<Result<Vec<_>, ClientError>>()?;
+                let responses = client.call_batch(calls).await?;
+
+                // Recorded as single calls, so that they're replayed whether batched or not
+                for ((method, params_value), response) in requests.into_iter().zip(&responses) {
+                    record_call(fixture, &method, params_value, false, response);
+                }
+                Ok(responses)
+            }
+            ReplayMode::Replay { .. } => {
+                let mut responses = Vec::with_capacity(calls.len());
+                for (method, params) in calls {
+                    responses.push(self.call(method, params, false).await?);
+                }
+                Ok(responses)
+            }
+        }
+    }
+}
+
+/// Appends the call and its response to the fixture, numbering it after the calls before it.
+fn record_call(
+    fixture: &Mutex<ReplayFixture>,
+    method: &str,
+    params: Option<Value>,
+    notification: bool,
+    response: &Result<Box<RawValue>, ErrorObjectOwned>,
+) {
+    let mut fixture = fixture.lock().unwrap_or_else(|e| e.into_inner());
+    let id = fixture.calls.len();
+    let mut request = json!({ "jsonrpc": "2.0", "method": method });
+    if !notification {
+        request["id"] = id.into();
+    }
+    if let Some(params) = params {
+        request["params"] = params;
+    }
+    let recorded = match response {
+        _ if notification => None,
+        Ok(result) => Some(json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+        Err(error) => Some(json!({ "jsonrpc": "2.0", "id": id, "error": error })),
+    };
+    fixture.calls.push(RecordedCall {
+        request,
+        response: recorded,
+    });
+}
+
+impl_client_t!(ReplayClient);
@@ -0,0 +1,283 @@
+use crate::{NotificationLayer, SpecModule};
+use jsonrpsee::{
+    http_client::HttpClient,
+    server::{
+        AlreadyStoppedError, Server, ServerHandle,
+        middleware::rpc::{
+            Batch, MethodResponse, Notification, Request, RpcServiceBuilder, RpcServiceT,
+        },
+    },
+    ws_client::{WsClient, WsClientBuilder},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// Sets up a test JSON-RPC server and client for the provided `SpecModule`.
+///
+/// # Type Parameters
+/// - `Context`: The context type that must implement `Send` and `Sync` traits and have a static lifetime.
+///
+/// # Arguments
+/// - `module`: The `SpecModule` to be tested, which will be converted into a JSON-RPC module.
+///
+/// # Returns
+/// - `Ok(TestServer<HttpClient>)`: The running server, with an HTTP client connected to it.
+/// - `Err(std::io::error::Error)`: An error if the server setup fails.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{SpecModule, test_server};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let module = SpecModule::new(());
+///     let server = test_server(module).await?;
+///     println!("Test server running at: {}", server.addr());
+///     Ok(())
+/// }
+/// ```
+pub async fn test_server<Context: Send + Sync + 'static>(
+    module: SpecModule<Context>,
+) -> std::io::Result<TestServer<HttpClient>> {
+    TestServer::start(module, |addr| async move {
+        // Create an HTTP client to interact with the test server.
+        jsonrpsee::http_client::HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .map_err(std::io::Error::other)
+    })
+    .await
+}
+
+/// Sets up a test JSON-RPC server and WebSocket client for the provided `SpecModule`, e.g. to test
+/// subscriptions or notifications over a persistent connection.
+///
+/// # Type Parameters
+/// - `Context`: The context type that must implement `Send` and `Sync` traits and have a static lifetime.
+///
+/// # Arguments
+/// - `module`: The `SpecModule` to be tested, which will be converted into a JSON-RPC module.
+///
+/// # Returns
+/// - `Ok(TestServer<WsClient>)`: The running server, with a WebSocket client connected to it.
+/// - `Err(std::io::error::Error)`: An error if the server setup or the connection fails.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{SpecModule, test_server_ws};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let module = SpecModule::new(());
+///     let server = test_server_ws(module).await?;
+///     println!("Test server running at: {}", server.addr());
+///     Ok(())
+/// }
+/// ```
+pub async fn test_server_ws<Context: Send + Sync + 'static>(
+    module: SpecModule<Context>,
+) -> std::io::Result<TestServer<WsClient>> {
+    TestServer::start(module, |addr| async move {
+        // Connect a WebSocket client to the test server, which serves both HTTP and WebSocket.
+        WsClientBuilder::default()
+            .build(format!("ws://{addr}"))
+            .await
+            .map_err(std::io::Error::other)
+    })
+    .await
+}
+
+/// A running test server, created by [`test_server`] or [`test_server_ws`], with a client
+/// connected to it. Every call made to the server is recorded.
+///
+/// The server stops when this is dropped, or explicitly with [`TestServer::stop`].
+///
+/// # Type Parameters
+/// - `Client`: The client connected to the server.
+pub struct TestServer<Client = HttpClient> {
+    client: Client,
+    addr: SocketAddr,
+    handle: ServerHandle,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl<Client> TestServer<Client> {
+    /// Starts a server for the `SpecModule` on a random available port, connecting a client
+    /// created by `connect` from the address of the server.
+    async fn start<Context, Connect>(
+        module: SpecModule<Context>,
+        connect: impl FnOnce(SocketAddr) -> Connect,
+    ) -> std::io::Result<Self>
+    where
+        Context: Send + Sync + 'static,
+        Connect: Future<Output = std::io::Result<Client>>,
+    {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        // Applies the middleware scoped to the methods of the module
+        let spec_layer = module.rpc_middleware();
+
+        // Build a new JSON-RPC server bound to a random available port.
+        let server = Server::builder()
+            .set_rpc_middleware(
+                RpcServiceBuilder::new()
+                    .layer(RecordLayer {
+                        calls: calls.clone(),
+                    })
+                    .layer(NotificationLayer)
+                    .layer(spec_layer),
+            )
+            .build(
+                "127.0.0.1:0"
+                    .parse::<SocketAddr>()
+                    .map_err(std::io::Error::other)?,
+            )
+            .await?;
+
+        // Convert the provided `SpecModule` into a JSON-RPC module.
+        let module = module.into_jsonrpsee_module();
+
+        // Retrieve the local address of the server.
+        let addr = server.local_addr()?;
+        // Start the server with the provided module.
+        let handle = server.start(module);
+        // Ensure the server handle indicates the server is running.
+        assert!(!handle.is_stopped());
+
+        let client = connect(addr).await?;
+
+        Ok(TestServer {
+            client,
+            addr,
+            handle,
+            calls,
+        })
+    }
+
+    /// Returns the client connected to the server.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops the server, waiting until it has shut down.
+    ///
+    /// # Returns
+    /// - `Ok(())`: The server has shut down.
+    /// - `Err(AlreadyStoppedError)`: An error if the server was already stopped.
+    pub async fn stop(&self) -> Result<(), AlreadyStoppedError> {
+        self.handle.stop()?;
+        self.handle.clone().stopped().await;
+        Ok(())
+    }
+
+    /// Returns whether the server has stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.handle.is_stopped()
+    }
+
+    /// Returns every call made to the server so far, in the order they were received, the calls
+    /// still running having no response yet.
+    pub fn recorded_calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A call made to a [`TestServer`] (or a [`ReplayClient`](super::ReplayClient)), as sent over the wire.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// The request, notification or batch (as an array).
+    pub request: Value,
+    /// The response, or `None` for a notification (or a batch of them).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+}
+
+/// Layer recording every call to the server (outside any other middleware).
+#[derive(Clone)]
+struct RecordLayer {
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl<S> tower::Layer<S> for RecordLayer {
+    type Service = RecordService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordService {
+            inner,
+            calls: self.calls.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RecordService<S> {
+    inner: S,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl<S> RecordService<S> {
+    fn record<'a>(
+        &self,
+        request: Value,
+        response: impl Future<Output = MethodResponse> + Send + 'a,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        // Listed once received, the response being filled in once it completes
+        let calls = self.calls.clone();
+        let index = {
+            let mut calls = calls.lock().unwrap_or_else(|e| e.into_inner());
+            calls.push(RecordedCall {
+                request,
+                response: None,
+            });
+            calls.len() - 1
+        };
+
+        async move {
+            let response = response.await;
+            let json = (!response.is_notification())
+                .then(|| serde_json::from_str(response.as_json().get()).ok())
+                .flatten();
+            calls.lock().unwrap_or_else(|e| e.into_inner())[index].response = json;
+            response
+        }
+    }
+}
+
+impl<S> RpcServiceT for RecordService<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let json = serde_json::to_value(&request).unwrap_or_default();
+        self.record(json, self.inner.call(request))
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let json = serde_json::to_value(&batch).unwrap_or_default();
+        self.record(json, self.inner.batch(batch))
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let json = serde_json::to_value(&notification).unwrap_or_default();
+        self.record(json, self.inner.notification(notification))
+    }
+}
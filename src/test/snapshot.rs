@@ -0,0 +1,106 @@
+use crate::OpenRpcSpec;
+use serde_json::Value;
+use std::path::Path;
+
+/// Asserts that the specification of a `SpecModule` matches a committed JSON snapshot, so any
+/// change to the published contract (e.g. of a schema) shows up as a readable diff.
+///
+/// The path is relative to the manifest directory of the crate using the macro. Set the
+/// `OPENSPEC_UPDATE=1` environment variable to create or update the snapshot instead.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{SpecModule, assert_spec_snapshot};
+///
+/// let module = SpecModule::new(());
+/// assert_spec_snapshot!(module, "tests/snapshots/api.json");
+/// ```
+#[macro_export]
+macro_rules! assert_spec_snapshot {
+    ($module:expr, $path:expr $(,)?) => {
+        $crate::test::assert_spec_snapshot_at(
+            $module.spec(),
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+        )
+    };
+}
+
+/// The environment variable which, when set to `1`, makes snapshots be updated instead of asserted.
+pub const SNAPSHOT_UPDATE_VAR: &str = "OPENSPEC_UPDATE";
+
+/// Asserts that the specification matches the JSON snapshot at `path`, see [`assert_spec_snapshot!`].
+///
+/// # Panics
+/// If the snapshot is missing or differs, listing every difference by its JSON pointer.
+#[track_caller]
+pub fn assert_spec_snapshot_at(spec: &OpenRpcSpec, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = serde_json::to_value(spec).expect("spec should serialize");
+
+    if std::env::var(SNAPSHOT_UPDATE_VAR).is_ok_and(|value| value == "1") {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("snapshot directory should be created");
+        }
+        let json = serde_json::to_string_pretty(&actual).expect("spec should serialize");
+        std::fs::write(path, json + "\n").expect("snapshot should be written");
+        return;
+    }
+
+    let expected = match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str::<Value>(&json).expect("snapshot should be valid JSON"),
+        Err(e) => panic!(
+            "snapshot {} can't be read ({e}), run with {SNAPSHOT_UPDATE_VAR}=1 to create it",
+            path.display()
+        ),
+    };
+
+    let mut differences = Vec::new();
+    diff_json("", &expected, &actual, &mut differences);
+    if !differences.is_empty() {
+        panic!(
+            "spec differs from snapshot {} (- snapshot, + actual), run with {SNAPSHOT_UPDATE_VAR}=1 to update it:\n{}",
+            path.display(),
+            differences.join("\n")
+        );
+    }
+}
+
+/// Lists the differences between two JSON values, one line per JSON pointer.
+fn diff_json(pointer: &str, expected: &Value, actual: &Value, differences: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let pointer = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (expected.get(key), actual.get(key)) {
+                    (Some(expected), Some(actual)) => {
+                        diff_json(&pointer, expected, actual, differences)
+                    }
+                    (Some(expected), None) => differences.push(format!("- {pointer}: {expected}")),
+                    (None, Some(actual)) => differences.push(format!("+ {pointer}: {actual}")),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            for index in 0..expected.len().max(actual.len()) {
+                let pointer = format!("{pointer}/{index}");
+                match (expected.get(index), actual.get(index)) {
+                    (Some(expected), Some(actual)) => {
+                        diff_json(&pointer, expected, actual, differences)
+                    }
+                    (Some(expected), None) => differences.push(format!("- {pointer}: {expected}")),
+                    (None, Some(actual)) => differences.push(format!("+ {pointer}: {actual}")),
+                    (None, None) => {}
+                }
+            }
+        }
+        (expected, actual) if expected != actual => {
+            let pointer = if pointer.is_empty() { "/" } else { pointer };
+            differences.push(format!("- {pointer}: {expected}\n+ {pointer}: {actual}"));
+        }
+        _ => {}
+    }
+}
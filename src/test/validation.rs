@@ -0,0 +1,25 @@
+use crate::SpecModule;
+
+/// Asserts that the specification of a `SpecModule` has no problem, see
+/// [`SpecModule::validate_spec`], so a `#[test]` fails CI until it's fixed.
+///
+/// # Example
+/// ```no_run
+/// use openspec_jsonrpsee::{SpecModule, assert_spec_valid, spec};
+///
+/// #[test]
+/// fn test_spec_is_valid() {
+///     let mut module = SpecModule::new(());
+///     module.set_spec_info(spec::Info::builder().title("Users".into()).build());
+///     assert_spec_valid(&module);
+/// }
+/// ```
+///
+/// # Panics
+/// If any problem is found, listing each with the JSON pointer of the invalid value.
+#[track_caller]
+pub fn assert_spec_valid<Context: Send + Sync + 'static>(module: &SpecModule<Context>) {
+    if let Err(error) = module.validate_spec() {
+        panic!("{error}");
+    }
+}
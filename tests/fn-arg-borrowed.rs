@@ -0,0 +1,119 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{SpecModule, batch_request, rpc, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+pub struct TextClient {
+    client: jsonrpsee::http_client::HttpClient,
+}
+
+#[rpc(client = TextClient)]
+fn greet(name: &str, titles: &[String]) -> String {
+    format!("Hello {} {name}", titles.join(" "))
+}
+
+#[rpc(client = TextClient)]
+async fn total(values: &[u32], point: &Point) -> i64 {
+    values.iter().map(|value| *value as i64).sum::<i64>() + point.x + point.y
+}
+
+#[rpc]
+fn shout(text: Cow<'_, str>) -> String {
+    text.to_uppercase()
+}
+
+#[rpc(blocking)]
+fn count(text: &str, separator: &char) -> usize {
+    text.split(*separator).count()
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(Greet)?
+        .add_method(Total)?
+        .add_method(Shout)?
+        .add_method(Count)?;
+    Ok(module)
+}
+
+#[test]
+fn test_borrowed_param_schemas() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+    let params = |name: &str| {
+        let method = module
+            .spec()
+            .methods
+            .iter()
+            .find(|method| method.name == name)
+            .expect("method should be in the spec");
+        method
+            .params
+            .iter()
+            .map(|param| serde_json::to_value(&param.schema).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // Described as their owned version
+    let greet = params("greet");
+    assert_eq!(greet[0]["type"], "string");
+    assert_eq!(greet[1]["type"], "array");
+    assert_eq!(greet[1]["items"]["type"], "string");
+    let total = params("total");
+    assert_eq!(total[0]["items"]["type"], "integer");
+    assert_eq!(total[1]["properties"]["x"]["type"], "integer");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_borrowed_params() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module()?).await?;
+    let client = server.client();
+
+    let titles = vec!["Dr.".to_string(), "Prof.".to_string()];
+    assert_eq!(
+        Greet::request(client, "Jane", &titles).await?,
+        "Hello Dr. Prof. Jane"
+    );
+    assert_eq!(
+        Total::request(client, &[1, 2, 3], &Point { x: 10, y: -4 }).await?,
+        12
+    );
+    assert_eq!(Shout::request(client, "hey".into()).await?, "HEY");
+    assert_eq!(Count::request(client, "a,b,c", &',').await?, 3);
+    // Escaped strings can't be borrowed from the request, so they're deserialized owned
+    assert_eq!(
+        Greet::request(client, "\"Jane\"\n", &[]).await?,
+        "Hello  \"Jane\"\n"
+    );
+    assert_eq!(Shout::request(client, "a\\b".into()).await?, "A\\B");
+
+    let text_client = TextClient {
+        client: client.clone(),
+    };
+    assert_eq!(text_client.greet("John", &[]).await?, "Hello  John");
+    assert_eq!(text_client.total(&[5], &Point { x: 0, y: 0 }).await?, 5);
+
+    let (greeting, count) = batch_request(
+        client,
+        (
+            Greet::batch_item("Alice", &titles[..1]),
+            Count::batch_item("a b", &' '),
+        ),
+    )
+    .await?;
+    assert_eq!(greeting?, "Hello Dr. Alice");
+    assert_eq!(count?, 2);
+
+    Ok(())
+}
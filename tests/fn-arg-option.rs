@@ -22,7 +22,8 @@ async fn test_handles_none() {
     assert_eq!(arg_type, Some(&json!(["string", "null"])));
     assert_eq!(spec.methods[0].params[0].required, Some(true));
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = OptionalArg::request_unchecked(&client, None).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = OptionalArg::request_unchecked(client, None).await;
     assert_eq!("Response: None".to_string(), response)
 }
@@ -0,0 +1,151 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{SpecModule, rpc, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Meters(f64);
+
+pub struct GeoClient {
+    client: jsonrpsee::http_client::HttpClient,
+}
+
+#[rpc(client = GeoClient)]
+fn manhattan(Point { x, y }: Point, (dx, dy): (i64, i64)) -> i64 {
+    (x - dx).abs() + (y - dy).abs()
+}
+
+#[rpc]
+fn scale(Meters(meters): Meters, #[param(name = "factor")] (times, _): (f64, String)) -> f64 {
+    meters * times
+}
+
+#[rpc]
+fn shift(#[param(name = "point-to-shift")] Point { x, y }: Point, mut by: i64, _: bool) -> Point {
+    by *= 2;
+    Point {
+        x: x + by,
+        y: y + by,
+    }
+}
+
+// The context isn't a param, so it doesn't count in the position of the params after it
+#[rpc]
+fn offset(#[context] origin: &Point, (dx, dy): (i64, i64)) -> Point {
+    Point {
+        x: origin.x + dx,
+        y: origin.y + dy,
+    }
+}
+
+struct Map {
+    origin: Point,
+}
+
+#[rpc]
+impl Map {
+    #[rpc]
+    fn distance(&self, #[param(name = "to")] Point { x, y }: Point) -> i64 {
+        (x - self.origin.x).abs() + (y - self.origin.y).abs()
+    }
+
+    #[rpc]
+    fn translate(&self, (dx, dy): (i64, i64)) -> i64 {
+        self.origin.x + dx + self.origin.y + dy
+    }
+}
+
+fn param_names(module: &SpecModule<impl Send + Sync + 'static>, name: &str) -> Vec<String> {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+        .params
+        .iter()
+        .map(|param| param.name.clone())
+        .collect()
+}
+
+#[test]
+fn test_pattern_param_names() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(Manhattan)?
+        .add_method(Scale)?
+        .add_method(Shift)?;
+
+    // Struct patterns are named after their type, others after their position among the params
+    assert_eq!(param_names(&module, "manhattan"), ["point", "arg1"]);
+    assert_eq!(param_names(&module, "scale"), ["meters", "factor"]);
+    assert_eq!(
+        param_names(&module, "shift"),
+        ["point-to-shift", "by", "arg2"]
+    );
+
+    let module = SpecModule::from_service(Map {
+        origin: Point { x: 1, y: 1 },
+    })?;
+    assert_eq!(param_names(&module, "distance"), ["to"]);
+    // Neither `&self` nor a `#[context]` counts in the positions
+    assert_eq!(param_names(&module, "translate"), ["arg0"]);
+    let mut module = SpecModule::new(Point { x: 1, y: 2 });
+    module.add_method(Offset)?;
+    assert_eq!(param_names(&module, "offset"), ["arg0"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pattern_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(Manhattan)?
+        .add_method(Scale)?
+        .add_method(Shift)?;
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    assert_eq!(
+        Manhattan::request(client, Point { x: 3, y: -1 }, (1, 1)).await?,
+        4
+    );
+    assert_eq!(
+        Scale::request(client, Meters(1.5), (2.0, "ignored".into())).await?,
+        3.0
+    );
+    let shifted = Shift::request(client, Point { x: 1, y: 2 }, 1, true).await?;
+    assert_eq!((shifted.x, shifted.y), (3, 4));
+
+    let geo_client = GeoClient {
+        client: client.clone(),
+    };
+    assert_eq!(geo_client.manhattan(Point { x: 0, y: 0 }, (2, 2)).await?, 4);
+
+    let module = SpecModule::from_service(Map {
+        origin: Point { x: 1, y: 1 },
+    })?;
+    let server = test_server(module).await?;
+    assert_eq!(
+        MapDistance::request(server.client(), Point { x: 4, y: 0 }).await?,
+        4
+    );
+    assert_eq!(MapTranslate::request(server.client(), (1, 2)).await?, 5);
+
+    let mut module = SpecModule::new(Point { x: 1, y: 2 });
+    module.add_method(Offset)?;
+    let server = test_server(module).await?;
+    let offset = Offset::request(server.client(), (1, 1)).await?;
+    assert_eq!((offset.x, offset.y), (2, 3));
+
+    Ok(())
+}
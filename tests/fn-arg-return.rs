@@ -18,8 +18,9 @@ async fn test_handles_simple_args() {
         .add_method(SimpleArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = SimpleArgs::request_unchecked(&client, "hello".into()).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = SimpleArgs::request_unchecked(client, "hello".into()).await;
     assert_eq!("Response: hello".to_string(), response)
 }
 
@@ -43,13 +44,14 @@ async fn test_handles_struct_args() {
         .add_method(StructArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
     let user = User {
         id: 123,
         name: "John".to_string(),
     };
-    let response = StructArgs::request_unchecked(&client, user.clone()).await;
+    let response = StructArgs::request_unchecked(client, user.clone()).await;
     assert_ne!(user, response);
     assert_eq!(user.id, response.id);
     assert_eq!("123-John", response.name);
@@ -71,14 +73,15 @@ async fn test_handles_multiple_args() {
         .add_method(MultipleArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
     let mut user = User {
         id: 123,
         name: "John".to_string(),
     };
     let prefix = "prefix".to_string();
-    let response = MultipleArgs::request_unchecked(&client, user.clone(), prefix.clone()).await;
+    let response = MultipleArgs::request_unchecked(client, user.clone(), prefix.clone()).await;
 
     user.name = format!("{}-{}", user.name, prefix);
     assert_eq!(user, response);
@@ -110,7 +113,8 @@ async fn test_handles_struct_in_struct() {
         .add_method(StructInStructArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
     let value = StructA {
         name: "a".to_string(),
@@ -118,7 +122,7 @@ async fn test_handles_struct_in_struct() {
             name: "b".to_string(),
         },
     };
-    let response = StructInStructArgs::request_unchecked(&client, value.clone()).await;
+    let response = StructInStructArgs::request_unchecked(client, value.clone()).await;
     assert_eq!(value.b, response);
 }
 
@@ -137,9 +141,10 @@ async fn test_handles_reference_args() {
         .add_method(ReferenceArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
-    let response = ReferenceArgs::request_unchecked(&client, "hello").await;
+    let response = ReferenceArgs::request_unchecked(client, "hello").await;
     assert_eq!(response, "Reference: hello");
 }
 
@@ -158,9 +163,10 @@ async fn test_handles_vector_args() {
         .add_method(VectorArgs)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
     let values = vec!["one".to_string(), "two".to_string(), "three".to_string()];
-    let response = VectorArgs::request_unchecked(&client, values.clone()).await;
+    let response = VectorArgs::request_unchecked(client, values.clone()).await;
     assert_eq!(response, values.len());
 }
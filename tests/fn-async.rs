@@ -18,8 +18,9 @@ pub async fn test_loads() {
     module
         .add_method(AsyncDoSomething)
         .expect("proof of concept should be able to register");
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = AsyncDoSomething::request_unchecked(&client, "hello".into()).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = AsyncDoSomething::request_unchecked(client, "hello".into()).await;
     assert_eq!("hello async world".to_string(), response)
 }
 
@@ -79,23 +80,24 @@ pub async fn test_mutate_async() -> Result<(), Box<dyn std::error::Error>> {
         .add_method(RegisterUser)?
         .add_method(RegisterUserRef)?;
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
 
     // No users
-    let response = GetUser::request_unchecked(&client, 1).await;
+    let response = GetUser::request_unchecked(client, 1).await;
     assert_eq!(response, None);
 
     // First user inserts & reads
-    let response = RegisterUser::request_unchecked(&client, "John".into(), "password".into()).await;
+    let response = RegisterUser::request_unchecked(client, "John".into(), "password".into()).await;
     assert!(response.is_ok());
-    let response = GetUser::request_unchecked(&client, 1).await;
+    let response = GetUser::request_unchecked(client, 1).await;
     assert_ne!(response, None);
 
     // Second user expands on state
     let response =
-        RegisterUserRef::request_unchecked(&client, "Jane".into(), "password".into()).await;
+        RegisterUserRef::request_unchecked(client, "Jane".into(), "password".into()).await;
     assert!(response.is_ok());
-    let response = GetUser::request_unchecked(&client, 2).await;
+    let response = GetUser::request_unchecked(client, 2).await;
     assert_ne!(response, None);
 
     Ok(())
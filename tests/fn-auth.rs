@@ -0,0 +1,184 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{
+    core::ClientError,
+    server::middleware::rpc::{Batch, MethodResponse, Notification, Request, RpcServiceT},
+};
+use openspec_jsonrpsee::{
+    SpecModule, UNAUTHORIZED_CODE, UNAUTHORIZED_MSG, rpc, spec, test_in_process, test_server,
+};
+use serde_json::json;
+use std::future::Future;
+
+/// The scopes granted to the caller, as inserted in the extensions by the `Grant` layer.
+#[derive(Clone)]
+struct Granted(&'static [&'static str]);
+
+/// Grants scopes to every call, standing in for a middleware checking credentials.
+#[derive(Clone)]
+struct Grant(&'static [&'static str]);
+
+impl<S> tower::Layer<S> for Grant {
+    type Service = GrantService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrantService {
+            inner,
+            scopes: self.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GrantService<S> {
+    inner: S,
+    scopes: &'static [&'static str],
+}
+
+impl<S> RpcServiceT for GrantService<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(
+        &self,
+        mut request: Request<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        request.extensions_mut().insert(Granted(self.scopes));
+        self.inner.call(request)
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.inner.batch(batch)
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.inner.notification(notification)
+    }
+}
+
+/// Deletes a user
+#[rpc(auth = "admin")]
+fn delete_user(id: u32) -> u32 {
+    id
+}
+
+#[rpc(requires = ["users:read", "users:audit"])]
+async fn get_user(id: u32) -> u32 {
+    id
+}
+
+#[rpc]
+fn ping() -> String {
+    "pong".into()
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(DeleteUser)?
+        .add_method(GetUser)?
+        .add_method(Ping)?;
+    Ok(module)
+}
+
+fn assert_unauthorized(error: ClientError, required: &[&str]) {
+    match error {
+        ClientError::Call(error) => {
+            assert_eq!(error.code(), UNAUTHORIZED_CODE);
+            // Not mistaken for an unknown server error
+            assert_ne!(error.code(), jsonrpsee::types::error::UNKNOWN_ERROR_CODE);
+            assert_eq!(error.message(), UNAUTHORIZED_MSG);
+            assert_eq!(
+                error.data().map(|data| data.get()),
+                Some(json!({ "required": required }).to_string().as_str())
+            );
+        }
+        error => panic!("expected an unauthorized error, got {error:?}"),
+    }
+}
+
+#[test]
+fn test_spec_records_scopes() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+    let method = |name: &str| -> spec::Method {
+        module
+            .spec()
+            .methods
+            .iter()
+            .find(|method| method.name == name)
+            .cloned()
+            .expect("method should be in the spec")
+    };
+
+    let delete_user = method("delete_user");
+    assert_eq!(delete_user.required_scopes(), ["admin"]);
+    assert_eq!(
+        delete_user.extensions.get(spec::extension::SECURITY),
+        Some(&json!(["admin"]))
+    );
+    assert_eq!(
+        delete_user.description.as_deref(),
+        Some("**Requires** the scopes: `admin`")
+    );
+    assert_eq!(delete_user.summary.as_deref(), Some("Deletes a user"));
+
+    let get_user = method("get_user");
+    assert_eq!(get_user.required_scopes(), ["users:read", "users:audit"]);
+
+    let ping = method("ping");
+    assert!(ping.required_scopes().is_empty());
+    assert!(!ping.extensions.contains_key(spec::extension::SECURITY));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_calls_denied_without_authorizer() -> Result<(), Box<dyn std::error::Error>> {
+    let client = test_in_process(module()?);
+
+    let error = DeleteUser::request(&client, 1).await.unwrap_err();
+    assert_unauthorized(error, &["admin"]);
+    let error = GetUser::request(&client, 1).await.unwrap_err();
+    assert_unauthorized(error, &["users:read", "users:audit"]);
+    assert_eq!(Ping::request(&client).await?, "pong");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_authorizer_reads_extensions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = module()?;
+    module.set_authorizer(|request: &openspec_jsonrpsee::AuthorizationRequest| {
+        let granted = request.extensions.get::<Granted>();
+        request
+            .scopes
+            .iter()
+            .all(|scope| granted.is_some_and(|Granted(granted)| granted.contains(&scope.as_str())))
+    });
+    module
+        .layer_for_method("get_user", Grant(&["users:read", "users:audit"]))
+        .layer_for_method("delete_user", Grant(&["users:read"]));
+
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    assert_eq!(GetUser::request(client, 7).await?, 7);
+    let error = DeleteUser::request(client, 7).await.unwrap_err();
+    assert_unauthorized(error, &["admin"]);
+    assert_eq!(Ping::request(client).await?, "pong");
+
+    Ok(())
+}
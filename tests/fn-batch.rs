@@ -0,0 +1,91 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::core::{ClientError, client::ClientT, params::BatchRequestBuilder};
+use openspec_jsonrpsee::{BatchItem, SpecModule, batch_request, rpc, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[rpc]
+fn get_user(id: u32) -> User {
+    User {
+        id,
+        name: format!("user-{id}"),
+    }
+}
+
+#[rpc]
+async fn count_users() -> usize {
+    42
+}
+
+#[rpc]
+/// Never registered with the module, so calls to it fail.
+fn unregistered() -> String {
+    "unreachable".into()
+}
+
+#[tokio::test]
+async fn test_tuple_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?.add_method(CountUsers)?;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+
+    let (user, count, failed) = batch_request(
+        client,
+        (
+            GetUser::batch_item(7),
+            CountUsers::batch_item(),
+            Unregistered::batch_item(),
+        ),
+    )
+    .await?;
+
+    assert_eq!(user?, get_user(7));
+    assert_eq!(count?, 42);
+    assert!(matches!(failed, Err(ClientError::Call(e)) if e.code() == -32601));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vec_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+
+    let users = batch_request(client, (1..=3).map(GetUser::batch_item).collect::<Vec<_>>())
+        .await?
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(users, vec![get_user(1), get_user(2), get_user(3)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_untyped_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+
+    let mut builder = BatchRequestBuilder::new();
+    GetUser::batch_item(5).insert_into(&mut builder)?;
+    let mut response = client
+        .batch_request::<serde_json::Value>(builder)
+        .await?
+        .into_iter();
+    let user = BatchItem::<User>::decode(response.next().expect("one entry"))?;
+    assert_eq!(user, get_user(5));
+
+    Ok(())
+}
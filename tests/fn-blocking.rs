@@ -0,0 +1,74 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{
+    RpcMethod, ServerHandler, SpecModule, rpc, test::test_server, test_in_process,
+};
+use std::time::{Duration, Instant};
+
+/// Generates a report, hogging the thread it runs on
+#[rpc(blocking)]
+fn generate_report(#[context] title: &str, millis: u64) -> String {
+    std::thread::sleep(Duration::from_millis(millis));
+    format!("{title} ({millis}ms)")
+}
+
+#[rpc(blocking)]
+fn count_words(text: String) -> usize {
+    text.split_whitespace().count()
+}
+
+#[rpc]
+async fn ping(#[context] _title: &str) -> String {
+    "pong".into()
+}
+
+#[test]
+fn test_blocking_handler() {
+    assert!(matches!(
+        RpcMethod::<String, String>::handler(&GenerateReport),
+        ServerHandler::Blocking(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_blocking_call() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new("Sales".to_string());
+    module.add_method(GenerateReport)?;
+    let server = test_server(module).await?;
+    assert_eq!(
+        GenerateReport::request(server.client(), 1).await?,
+        "Sales (1ms)"
+    );
+
+    let mut module = SpecModule::new(());
+    module.add_method(CountWords)?;
+    let server = test_server(module).await?;
+    assert_eq!(
+        CountWords::request(server.client(), "a b  c".into()).await?,
+        3
+    );
+
+    Ok(())
+}
+
+// The test runtime has a single thread, which blocking methods don't hog
+#[tokio::test]
+async fn test_blocking_call_doesnt_stall() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new("Sales".to_string());
+    module.add_method(GenerateReport)?.add_method(Ping)?;
+    let client = test_in_process(module);
+
+    let start = Instant::now();
+    let (report, pong) = tokio::join!(GenerateReport::request(&client, 300), async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let pong = Ping::request(&client).await;
+        (pong, start.elapsed())
+    });
+    assert_eq!(report?, "Sales (300ms)");
+    let (pong, elapsed) = pong;
+    assert_eq!(pong?, "pong");
+    assert!(elapsed < Duration::from_millis(250), "{elapsed:?}");
+
+    Ok(())
+}
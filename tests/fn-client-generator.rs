@@ -26,8 +26,11 @@ async fn test_handles_simple_args() {
     module.add_method(SimpleMethod).unwrap();
     module.add_method(DifferentMethod).unwrap();
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let client = MyClient { client };
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let client = MyClient {
+        client: client.clone(),
+    };
 
     let response = client.simple_method().await.expect("should work");
     assert_eq!(response, "Hello, World!");
@@ -49,7 +52,8 @@ async fn test_unit_client() {
     let mut module = SpecModule::new(());
     module.add_method(AnotherMethod).unwrap();
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let res = UnitClient(client).another_method().await.unwrap();
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let res = UnitClient(client.clone()).another_method().await.unwrap();
     assert_eq!(res, "Hello, Another World!");
 }
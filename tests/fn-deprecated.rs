@@ -0,0 +1,61 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{SpecModule, rpc, test::test_server};
+use std::sync::{Arc, Mutex};
+
+#[rpc(replaced_by = "current_method")]
+#[deprecated(since = "1.0.0", note = "use current_method")]
+fn retired_method(value: String) -> String {
+    format!("Retired: {value}")
+}
+
+#[rpc]
+async fn current_method(value: String) -> String {
+    format!("Current: {value}")
+}
+
+#[rpc]
+#[deprecated]
+async fn retired_async_method() -> String {
+    "Retired async".into()
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn test_deprecated_call_hook() -> Result<(), Box<dyn std::error::Error>> {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let mut module = SpecModule::new(());
+    module
+        .add_method(RetiredMethod)?
+        .add_method(CurrentMethod)?;
+    let hook_calls = calls.clone();
+    module.on_deprecated_call(move |call| {
+        hook_calls.lock().unwrap().push((
+            call.method.name.clone(),
+            call.method.replaced_by().map(String::from),
+        ));
+    });
+    module.add_method(RetiredAsyncMethod)?;
+
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+
+    let response = RetiredMethod::request_unchecked(client, "a".into()).await;
+    assert_eq!(response, "Retired: a");
+    let response = CurrentMethod::request_unchecked(client, "b".into()).await;
+    assert_eq!(response, "Current: b");
+    let response = RetiredAsyncMethod::request_unchecked(client).await;
+    assert_eq!(response, "Retired async");
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            ("retired_method".into(), Some("current_method".into())),
+            ("retired_async_method".into(), None),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{RpcMethod, SpecModule, batch_request, rpc, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+trait Entity: JsonSchema + Serialize + Clone + for<'de> Deserialize<'de> + Send + 'static {
+    fn all() -> Vec<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct User {
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Post {
+    title: String,
+    likes: u32,
+}
+
+impl Entity for User {
+    fn all() -> Vec<Self> {
+        vec![
+            User {
+                name: "Jane".into(),
+            },
+            User {
+                name: "John".into(),
+            },
+        ]
+    }
+}
+
+impl Entity for Post {
+    fn all() -> Vec<Self> {
+        vec![Post {
+            title: "Hello".into(),
+            likes: 3,
+        }]
+    }
+}
+
+/// Lists the entities, up to the limit
+#[rpc]
+fn list<T: Entity>(limit: usize) -> Vec<T> {
+    T::all().into_iter().take(limit).collect()
+}
+
+#[rpc]
+async fn get<T>(index: usize) -> Option<T>
+where
+    T: Entity,
+{
+    T::all().into_iter().nth(index)
+}
+
+#[rpc]
+fn ones<const N: usize>() -> Vec<u8> {
+    vec![1; N]
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(List::<User>::named("users.list"))?
+        .add_method(List::<Post>::named("posts.list"))?
+        .add_method(Get::<User>::named("users.get"))?
+        .add_method(Ones::<2>::named("two_ones"))?;
+    Ok(module)
+}
+
+fn result_schema(module: &SpecModule, name: &str) -> serde_json::Value {
+    let method = module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec");
+    serde_json::to_value(&method.result.as_ref().unwrap().schema).unwrap()
+}
+
+#[test]
+fn test_generic_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+    let names: Vec<_> = module
+        .spec()
+        .methods
+        .iter()
+        .map(|method| method.name.as_str())
+        .collect();
+    assert_eq!(names, ["users.list", "posts.list", "users.get", "two_ones"]);
+
+    // Each instance has the schema of its own type
+    let users = result_schema(&module, "users.list");
+    let posts = result_schema(&module, "posts.list");
+    assert_ne!(users, posts);
+    assert_eq!(
+        users["items"]["properties"]["name"]["type"],
+        json!("string")
+    );
+    assert_eq!(
+        posts["items"]["properties"]["likes"]["type"],
+        json!("integer")
+    );
+
+    let list = List::<User>::named("users.list");
+    assert_eq!(RpcMethod::<(), Vec<User>>::name(&list), "users.list");
+    assert_eq!(
+        RpcMethod::<(), Vec<User>>::spec(&list).summary.as_deref(),
+        Some("Lists the entities, up to the limit")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generic_names_conflict() {
+    let mut module = SpecModule::new(());
+    module.add_method(List::<User>::named("list")).unwrap();
+    assert!(module.add_method(List::<Post>::named("list")).is_err());
+}
+
+#[tokio::test]
+async fn test_generic_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module()?).await?;
+    let client = server.client();
+
+    let users = List::<User>::named("users.list").request(client, 1).await?;
+    assert_eq!(
+        users,
+        [User {
+            name: "Jane".into()
+        }]
+    );
+    let posts = List::<Post>::named("posts.list")
+        .request(client, 10)
+        .await?;
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].likes, 3);
+
+    let user = Get::<User>::named("users.get").request(client, 1).await?;
+    assert_eq!(
+        user,
+        Some(User {
+            name: "John".into()
+        })
+    );
+    assert_eq!(Ones::<2>::named("two_ones").request(client).await?, [1, 1]);
+
+    let (users, user) = batch_request(
+        client,
+        (
+            List::<User>::named("users.list").batch_item(5),
+            Get::<User>::named("users.get").batch_item(7),
+        ),
+    )
+    .await?;
+    assert_eq!(users?.len(), 2);
+    assert_eq!(user?, None);
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::unused_unit)]
 
+use jsonrpsee::http_client::HttpClient;
+use openspec_jsonrpsee::test::test_server;
 use openspec_jsonrpsee::{SpecModule, rpc};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
 
 #[rpc]
 /// This is a doc comment for the do_something method.
@@ -16,3 +20,44 @@ pub fn test_jsonrpsee() {
         .add_method(DoSomething)
         .expect("proof of concept should be able to register");
 }
+
+#[rpc(notification)]
+/// Fire-and-forget method, the caller never waits for it to run.
+fn ping(#[context] ctx: &Arc<Notify>) {
+    ctx.notify_one();
+}
+
+struct NotifyingClient {
+    client: HttpClient,
+}
+
+#[rpc(notification, client = NotifyingClient)]
+fn ping_client(#[context] ctx: &Arc<Notify>) {
+    ctx.notify_one();
+}
+
+#[tokio::test]
+pub async fn test_notification() -> Result<(), Box<dyn std::error::Error>> {
+    let pinged = Arc::new(Notify::new());
+    let mut module = SpecModule::new(pinged.clone());
+    module.add_method(Ping)?.add_method(PingClient)?;
+
+    let spec = module.spec();
+    assert!(spec.methods[0].is_notification());
+    assert!(spec.methods[1].is_notification());
+
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+
+    Ping::notify(client).await?;
+    tokio::time::timeout(Duration::from_secs(1), pinged.notified()).await?;
+
+    NotifyingClient {
+        client: client.clone(),
+    }
+    .ping_client()
+    .await?;
+    tokio::time::timeout(Duration::from_secs(1), pinged.notified()).await?;
+
+    Ok(())
+}
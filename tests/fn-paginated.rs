@@ -0,0 +1,169 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use futures_util::{StreamExt, TryStreamExt};
+use openspec_jsonrpsee::{Page, PageRequest, SpecModule, rpc, spec, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Order {
+    id: u32,
+    customer: String,
+}
+
+const ORDER_COUNT: u32 = 7;
+
+/// Lists the orders of the customer
+#[rpc(paginated)]
+fn list_orders(customer: String, page: PageRequest<u32>) -> Page<Order, u32> {
+    let start = page.cursor.unwrap_or(0);
+    let end = (start + page.limit_or(3)).min(ORDER_COUNT);
+    let orders = (start..end)
+        .map(|id| Order {
+            id,
+            customer: customer.clone(),
+        })
+        .collect();
+    Page::new(orders, (end < ORDER_COUNT).then_some(end))
+}
+
+// Opaque `String` cursors by default
+#[rpc(paginated)]
+async fn list_tags(page: PageRequest) -> Page<String> {
+    match page.cursor.as_deref() {
+        None => Page::new(vec!["a".into(), "b".into()], Some("after-b".into())),
+        Some("after-b") => Page::last(vec!["c".into()]),
+        Some(_) => Page::last(vec![]),
+    }
+}
+
+// Never moves past the second page
+#[rpc(paginated)]
+fn list_stuck(page: PageRequest<u32>) -> Page<u32, u32> {
+    let cursor = page.cursor.unwrap_or(0);
+    Page::new(vec![cursor], Some(cursor.max(1)))
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(ListOrders)?
+        .add_method(ListTags)?
+        .add_method(ListStuck)?;
+    Ok(module)
+}
+
+fn method<'a>(module: &'a SpecModule, name: &str) -> &'a spec::Method {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+}
+
+#[test]
+fn test_paginated_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+
+    // The page request is described as the `cursor` and `limit` params
+    let list_orders = method(&module, "list_orders");
+    let params: Vec<_> = list_orders
+        .params
+        .iter()
+        .map(|param| param.name.as_str())
+        .collect();
+    assert_eq!(params, ["customer", "cursor", "limit"]);
+    let cursor = serde_json::to_value(&list_orders.params[1].schema)?;
+    assert_eq!(cursor["type"], json!(["integer", "null"]));
+
+    let result = serde_json::to_value(&list_orders.result.as_ref().unwrap().schema)?;
+    assert!(result["properties"]["items"].is_object());
+    assert!(result["properties"]["next_cursor"].is_object());
+
+    // Linked to the next page
+    let links = list_orders.links.as_ref().unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].name, "next_page");
+    assert_eq!(links[0].method, "list_orders");
+    let link_params = links[0].params.as_ref().unwrap();
+    assert_eq!(link_params["customer"], "$params.customer");
+    assert_eq!(link_params["cursor"], "$result.next_cursor");
+    assert_eq!(link_params["limit"], "$params.limit");
+
+    let list_tags = method(&module, "list_tags");
+    assert_eq!(list_tags.params.len(), 2);
+    assert_eq!(list_tags.links.as_ref().unwrap().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paginated_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module()?).await?;
+    let client = server.client();
+
+    let page = ListOrders::request(client, "jane".into(), PageRequest::first(Some(4))).await?;
+    assert_eq!(page.items.len(), 4);
+    assert_eq!(page.next_cursor, Some(4));
+
+    let page = ListOrders::request(
+        client,
+        "jane".into(),
+        PageRequest {
+            cursor: page.next_cursor,
+            limit: Some(4),
+        },
+    )
+    .await?;
+    assert_eq!(page.items.len(), 3);
+    assert_eq!(page.items[0].id, 4);
+    assert_eq!(page.next_cursor, None);
+
+    // Called with the raw `cursor` and `limit` params
+    let page: Page<Order, u32> = jsonrpsee::core::client::ClientT::request(
+        client,
+        "list_orders",
+        jsonrpsee::rpc_params!("joe", 6, None::<u32>),
+    )
+    .await?;
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].customer, "joe");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paginate() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module()?).await?;
+    let client = server.client();
+
+    // Follows the cursors until the last page
+    let orders: Vec<Order> = ListOrders::paginate(client, "jane".into(), Some(2))
+        .try_collect()
+        .await?;
+    let ids: Vec<u32> = orders.iter().map(|order| order.id).collect();
+    assert_eq!(ids, (0..ORDER_COUNT).collect::<Vec<_>>());
+
+    let tags: Vec<String> = ListTags::paginate(client, None).try_collect().await?;
+    assert_eq!(tags, ["a", "b", "c"]);
+
+    // Lazily fetched
+    let mut orders = std::pin::pin!(ListOrders::paginate(client, "joe".into(), None));
+    let first = orders.next().await.unwrap()?;
+    assert_eq!(first.id, 0);
+
+    // Ends with an error once the next cursor is the one just sent, instead of never ending
+    let stuck: Vec<_> = ListStuck::paginate(client, None).collect().await;
+    assert_eq!(stuck.len(), 3);
+    assert_eq!(stuck[0].as_ref().ok(), Some(&0));
+    assert_eq!(stuck[1].as_ref().ok(), Some(&1));
+    assert!(matches!(
+        stuck[2],
+        Err(jsonrpsee::core::ClientError::Custom(_))
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{
+    core::{ClientError, RpcResult},
+    types::{ErrorObject, ErrorObjectOwned},
+};
+use openspec_jsonrpsee::{SpecModule, rpc, spec, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const NOT_FOUND: i32 = -32004;
+const INVALID_AMOUNT: i32 = -32010;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+fn user_errors() -> Vec<spec::Error> {
+    vec![spec::Error {
+        code: NOT_FOUND,
+        message: "User not found".into(),
+        data: None,
+    }]
+}
+
+/// Gets a user by its id
+#[rpc(errors = user_errors)]
+fn get_user(id: u32) -> RpcResult<User> {
+    match id {
+        1 => Ok(User {
+            id,
+            name: "Jane".into(),
+        }),
+        _ => Err(ErrorObject::owned(NOT_FOUND, "User not found", Some(id))),
+    }
+}
+
+#[rpc]
+async fn withdraw(amount: i64) -> Result<i64, ErrorObjectOwned> {
+    if amount <= 0 {
+        return Err(ErrorObject::owned(
+            INVALID_AMOUNT,
+            "Invalid amount",
+            None::<()>,
+        ));
+    }
+    Ok(100 - amount)
+}
+
+#[rpc(blocking)]
+fn checksum(data: Vec<u8>) -> RpcResult<u32> {
+    if data.is_empty() {
+        return Err(ErrorObject::owned(INVALID_AMOUNT, "No data", None::<()>));
+    }
+    Ok(data.iter().map(|byte| *byte as u32).sum())
+}
+
+// Other results are still wrapped in `Ok`
+#[rpc]
+fn parse(text: String) -> Result<u32, String> {
+    text.parse().map_err(|_| format!("`{text}` isn't a number"))
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(GetUser)?
+        .add_method(Withdraw)?
+        .add_method(Checksum)?
+        .add_method(Parse)?;
+    Ok(module)
+}
+
+fn method<'a>(module: &'a SpecModule, name: &str) -> &'a spec::Method {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+}
+
+fn result_schema(module: &SpecModule, name: &str) -> serde_json::Value {
+    serde_json::to_value(&method(module, name).result.as_ref().unwrap().schema).unwrap()
+}
+
+fn call_error(error: ClientError) -> ErrorObjectOwned {
+    match error {
+        ClientError::Call(error) => error,
+        error => panic!("expected a call error, got {error:?}"),
+    }
+}
+
+#[test]
+fn test_rpc_result_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+
+    // Described by the type of the success value
+    let user = result_schema(&module, "get_user");
+    assert_eq!(user["properties"]["name"]["type"], "string");
+    assert_eq!(result_schema(&module, "withdraw")["type"], "integer");
+    assert_eq!(result_schema(&module, "checksum")["type"], "integer");
+    assert!(result_schema(&module, "parse")["oneOf"].is_array());
+
+    let errors = method(&module, "get_user").errors.as_ref().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, NOT_FOUND);
+    assert_eq!(errors[0].message, "User not found");
+    assert!(method(&module, "withdraw").errors.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rpc_result_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module()?).await?;
+    let client = server.client();
+
+    assert_eq!(
+        GetUser::request(client, 1).await?,
+        User {
+            id: 1,
+            name: "Jane".into()
+        }
+    );
+    let error = call_error(GetUser::request(client, 2).await.unwrap_err());
+    assert_eq!(error.code(), NOT_FOUND);
+    assert_eq!(error.message(), "User not found");
+    assert_eq!(error.data().map(|data| data.get()), Some("2"));
+
+    assert_eq!(Withdraw::request(client, 30).await?, 70);
+    let error = call_error(Withdraw::request(client, -1).await.unwrap_err());
+    assert_eq!(error.code(), INVALID_AMOUNT);
+
+    assert_eq!(Checksum::request(client, vec![1, 2]).await?, 3);
+    let error = call_error(Checksum::request(client, vec![]).await.unwrap_err());
+    assert_eq!(error.message(), "No data");
+
+    assert_eq!(Parse::request(client, "7".into()).await?, Ok(7));
+    assert!(Parse::request(client, "seven".into()).await?.is_err());
+
+    Ok(())
+}
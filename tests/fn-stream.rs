@@ -0,0 +1,197 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use futures_util::stream;
+use jsonrpsee::ws_client::WsClientBuilder;
+use jsonrpsee::{
+    core::{ClientError, RpcResult},
+    types::ErrorObject,
+};
+use openspec_jsonrpsee::{RpcStream, SpecModule, rpc, spec, test_server_ws};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct LogLine {
+    index: usize,
+    text: String,
+}
+
+pub struct LogClient {
+    client: jsonrpsee::ws_client::WsClient,
+}
+
+/// Exports the logs, line by line
+#[rpc(client = LogClient)]
+fn export_logs(count: usize) -> RpcStream<LogLine> {
+    RpcStream::iter((0..count).map(|index| LogLine {
+        index,
+        text: format!("line {index}"),
+    }))
+}
+
+#[rpc]
+async fn countdown(from: u32) -> RpcStream<u32> {
+    RpcStream::new(stream::unfold(from, |count| async move {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        (count > 0).then(|| (count, count - 1))
+    }))
+}
+
+#[rpc]
+fn checked_range(start: u32, end: u32) -> RpcResult<RpcStream<u32>> {
+    if start > end {
+        return Err(ErrorObject::owned(-32010, "Invalid range", None::<()>));
+    }
+    Ok(RpcStream::iter(start..end))
+}
+
+#[rpc]
+fn parse_all(texts: Vec<String>) -> RpcStream<u32> {
+    RpcStream::try_new(stream::iter(texts.into_iter().map(|text| {
+        text.parse()
+            .map_err(|_| ErrorObject::owned(-32011, "Not a number", Some(text)))
+    })))
+}
+
+/// Notifies once dropped, i.e. once the stream holding it is dropped
+struct DropGuard(Arc<Notify>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.notify_one();
+    }
+}
+
+#[rpc]
+fn ticks(#[context] dropped: &Arc<Notify>) -> RpcStream<u64> {
+    let guard = DropGuard(dropped.clone());
+    RpcStream::new(stream::unfold((0, guard), |(tick, guard)| async move {
+        Some((tick, (tick + 1, guard)))
+    }))
+}
+
+fn module() -> Result<SpecModule, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(ExportLogs)?
+        .add_method(Countdown)?
+        .add_method(CheckedRange)?
+        .add_method(ParseAll)?;
+    Ok(module)
+}
+
+fn method<'a>(module: &'a SpecModule, name: &str) -> &'a spec::Method {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+}
+
+#[test]
+fn test_stream_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let module = module()?;
+
+    // The result describes the items
+    let export_logs = method(&module, "export_logs");
+    let schema = serde_json::to_value(&export_logs.result.as_ref().unwrap().schema)?;
+    assert_eq!(schema["properties"]["text"]["type"], "string");
+    assert_eq!(
+        export_logs.extensions[spec::extension::SUBSCRIPTION],
+        json!({
+            "notification": "export_logs",
+            "unsubscribe": "export_logs_unsubscribe",
+        })
+    );
+    let checked_range = method(&module, "checked_range");
+    let schema = serde_json::to_value(&checked_range.result.as_ref().unwrap().schema)?;
+    assert_eq!(schema["type"], "integer");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_items() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server_ws(module()?).await?;
+    let client = server.client();
+
+    let mut logs = ExportLogs::subscribe(client, 3).await?;
+    for index in 0..3 {
+        let line = logs.next().await.unwrap()?;
+        assert_eq!(line.index, index);
+        assert_eq!(line.text, format!("line {index}"));
+    }
+    // Closed once the stream ended
+    assert!(logs.next().await.is_none());
+
+    let countdown = Countdown::subscribe(client, 3).await?;
+    let counts: Vec<u32> = collect(countdown).await?;
+    assert_eq!(counts, [3, 2, 1]);
+
+    let log_client = LogClient {
+        client: WsClientBuilder::default()
+            .build(format!("ws://{}", server.addr()))
+            .await?,
+    };
+    let logs: Vec<LogLine> = collect(log_client.export_logs(2).await?).await?;
+    assert_eq!(logs.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server_ws(module()?).await?;
+    let client = server.client();
+
+    let range = CheckedRange::subscribe(client, 1, 4).await?;
+    assert_eq!(collect(range).await?, [1, 2, 3]);
+
+    // The subscription is rejected with the error
+    let error = CheckedRange::subscribe(client, 4, 1).await.unwrap_err();
+    let ClientError::Call(error) = error else {
+        panic!("expected a call error, got {error:?}");
+    };
+    assert_eq!(error.code(), -32010);
+
+    // An item error closes the subscription
+    let texts = vec!["1".into(), "two".into(), "3".into()];
+    let parsed = ParseAll::subscribe(client, texts).await?;
+    assert_eq!(collect(parsed).await?, [1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_unsubscribe() -> Result<(), Box<dyn std::error::Error>> {
+    let dropped = Arc::new(Notify::new());
+    let mut module = SpecModule::new(dropped.clone());
+    module.add_method(Ticks)?;
+    let server = test_server_ws(module).await?;
+    let client = server.client();
+
+    let mut ticks = Ticks::subscribe(client).await?;
+    assert_eq!(ticks.next().await.unwrap()?, 0);
+    assert_eq!(ticks.next().await.unwrap()?, 1);
+
+    // The stream is dropped once unsubscribed
+    ticks.unsubscribe().await?;
+    tokio::time::timeout(Duration::from_secs(1), dropped.notified()).await?;
+
+    Ok(())
+}
+
+async fn collect<T: serde::de::DeserializeOwned>(
+    mut subscription: jsonrpsee::core::client::Subscription<T>,
+) -> Result<Vec<T>, serde_json::Error> {
+    let mut items = Vec::new();
+    while let Some(item) = subscription.next().await {
+        items.push(item?);
+    }
+    Ok(items)
+}
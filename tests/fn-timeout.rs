@@ -0,0 +1,192 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{
+    core::ClientError,
+    http_client::{HeaderMap, HeaderValue, HttpClientBuilder},
+    server::{Server, middleware::rpc::RpcServiceBuilder},
+};
+use openspec_jsonrpsee::{
+    CancellationToken, DEADLINE_HEADER, DeadlineHeaderLayer, SpecModule, TIMEOUT_CODE,
+    format_duration, parse_duration, rpc, spec, test_in_process,
+};
+use serde_json::json;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[rpc(timeout = "100ms")]
+async fn sleep(millis: u64) -> u64 {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    millis
+}
+
+#[rpc]
+async fn wait(millis: u64) -> u64 {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    millis
+}
+
+#[rpc(timeout = "50ms")]
+async fn watch(#[cancellation] cancellation: CancellationToken, label: String) -> String {
+    let background = cancellation.clone();
+    tokio::spawn(async move {
+        background.cancelled().await;
+        CANCELLED.store(true, Ordering::SeqCst);
+    });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    label
+}
+
+#[rpc]
+fn ping() -> String {
+    "pong".into()
+}
+
+mod untimed {
+    use openspec_jsonrpsee::rpc;
+
+    /// Named like the `sleep` method, without a timeout of its own
+    #[rpc]
+    pub async fn sleep(millis: u64) -> u64 {
+        millis
+    }
+}
+
+fn method<'a>(module: &'a SpecModule, name: &str) -> &'a spec::Method {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+}
+
+fn assert_timed_out(error: ClientError, timeout_ms: u64) {
+    match error {
+        ClientError::Call(error) => {
+            assert_eq!(error.code(), TIMEOUT_CODE);
+            assert_eq!(
+                error.data().map(|data| data.get()),
+                Some(json!({ "timeoutMs": timeout_ms }).to_string().as_str())
+            );
+        }
+        error => panic!("expected a timeout error, got {error:?}"),
+    }
+}
+
+#[test]
+fn test_duration_format() {
+    assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+    assert_eq!(parse_duration("5s"), Some(Duration::from_secs(5)));
+    assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+    assert_eq!(parse_duration("5"), None);
+    assert_eq!(parse_duration("s"), None);
+    assert_eq!(parse_duration("5d"), None);
+    assert_eq!(parse_duration("0s"), None);
+    assert_eq!(parse_duration("0ms"), None);
+
+    assert_eq!(format_duration(Duration::from_millis(1500)), "1500ms");
+    assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+    assert_eq!(format_duration(Duration::from_secs(120)), "2m");
+    assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+}
+
+#[test]
+fn test_spec_records_timeouts() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Sleep)?.add_method(Wait)?;
+    module.set_default_timeout(Some(Duration::from_secs(5)));
+    module.add_method(Ping)?;
+
+    let timeout = |module: &SpecModule, name: &str| {
+        method(module, name)
+            .extensions
+            .get(spec::extension::TIMEOUT)
+            .cloned()
+    };
+    assert_eq!(timeout(&module, "sleep"), Some(json!("100ms")));
+    assert_eq!(timeout(&module, "wait"), Some(json!("5s")));
+    // Sync methods can't be interrupted
+    assert_eq!(timeout(&module, "ping"), None);
+
+    module.set_default_timeout(None);
+    assert_eq!(timeout(&module, "sleep"), Some(json!("100ms")));
+    assert_eq!(timeout(&module, "wait"), None);
+
+    // A method that fails to register doesn't replace the timeout of the one registered
+    assert!(module.add_method(untimed::Sleep).is_err());
+    module.set_default_timeout(Some(Duration::from_secs(5)));
+    assert_eq!(timeout(&module, "sleep"), Some(json!("100ms")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Sleep)?.add_method(Wait)?;
+    module.set_default_timeout(Some(Duration::from_millis(200)));
+    let client = test_in_process(module);
+
+    assert_eq!(Sleep::request(&client, 10).await?, 10);
+    assert_timed_out(Sleep::request(&client, 500).await.unwrap_err(), 100);
+
+    // The default applies to methods without their own timeout
+    assert_eq!(Wait::request(&client, 150).await?, 150);
+    assert_timed_out(Wait::request(&client, 500).await.unwrap_err(), 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancellation_on_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Watch)?;
+    let client = test_in_process(module);
+
+    let error = Watch::request(&client, "a".into()).await.unwrap_err();
+    assert_timed_out(error, 50);
+
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !CANCELLED.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("the token should be cancelled");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deadline_header() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Sleep)?.add_method(Wait)?;
+
+    let server = Server::builder()
+        .set_http_middleware(tower::ServiceBuilder::new().layer(DeadlineHeaderLayer))
+        .set_rpc_middleware(RpcServiceBuilder::new().layer(module.rpc_middleware()))
+        .build("127.0.0.1:0")
+        .await?;
+    let addr = server.local_addr()?;
+    let handle = server.start(module.into_jsonrpsee_module());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(DEADLINE_HEADER, HeaderValue::from_static("30ms"));
+    let client = HttpClientBuilder::default()
+        .set_headers(headers)
+        .build(format!("http://{addr}"))?;
+
+    // The hint shortens the timeout of the method, and applies to methods without one
+    assert_timed_out(Sleep::request(&client, 80).await.unwrap_err(), 30);
+    assert_timed_out(Wait::request(&client, 80).await.unwrap_err(), 30);
+    assert_eq!(Wait::request(&client, 5).await?, 5);
+
+    handle.stop()?;
+    Ok(())
+}
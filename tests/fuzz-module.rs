@@ -0,0 +1,142 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{
+    FuzzFailure, FuzzFailureKind, FuzzOptions, FuzzReport, SpecModule, fuzz_module, rpc,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct User {
+    id: u32,
+    name: String,
+    email: Option<String>,
+    roles: Vec<Role>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum Role {
+    Admin,
+    Member,
+}
+
+#[rpc]
+fn rename_user(mut user: User, name: String) -> User {
+    user.name = name;
+    user
+}
+
+#[rpc]
+fn count_chars(values: HashMap<String, char>, limit: Option<u8>) -> usize {
+    values.len().min(limit.map_or(usize::MAX, usize::from))
+}
+
+#[rpc]
+fn no_args() -> bool {
+    true
+}
+
+#[rpc]
+async fn async_sum(values: Vec<i32>) -> i64 {
+    values.into_iter().map(i64::from).sum()
+}
+
+#[tokio::test]
+async fn test_fuzz_well_behaved_module() {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(RenameUser)
+        .expect("method should register")
+        .add_method(CountChars)
+        .expect("method should register")
+        .add_method(NoArgs)
+        .expect("method should register")
+        .add_method(AsyncSum)
+        .expect("method should register");
+
+    let report = fuzz_module(module, FuzzOptions::default()).await;
+    report.assert_ok();
+    assert!(report.calls > 4 * 100);
+}
+
+#[rpc]
+fn divide(dividend: i32, divisor: i32) -> i32 {
+    dividend / divisor
+}
+
+#[tokio::test]
+async fn test_fuzz_finds_panics() {
+    let mut module = SpecModule::new(());
+    module.add_method(Divide).expect("method should register");
+
+    // Bounds (such as `0`) are favoured, so division by zero is found
+    let report = fuzz_module(module, FuzzOptions::builder().iterations(50).build()).await;
+    let failure = report
+        .failures
+        .iter()
+        .find(|failure| matches!(failure.kind, FuzzFailureKind::Panic(_)))
+        .expect("division by zero should be found");
+    assert_eq!(failure.method, "divide");
+    assert_eq!(failure.params.as_ref().unwrap()[1], 0);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Settlement {
+    #[schemars(range(max = -5000.0))]
+    debt: f64,
+    #[schemars(extend("exclusiveMinimum" = 0, "exclusiveMaximum" = 1))]
+    rate: f64,
+    #[schemars(range(max = -5000))]
+    floor: i64,
+}
+
+#[rpc]
+fn settle(#[context] within_bounds: &Arc<AtomicUsize>, settlement: Settlement) -> bool {
+    if settlement.debt <= -5000.0
+        && settlement.rate > 0.0
+        && settlement.rate < 1.0
+        && settlement.floor <= -5000
+    {
+        within_bounds.fetch_add(1, Ordering::SeqCst);
+    }
+    true
+}
+
+#[tokio::test]
+async fn test_fuzz_within_bounds() {
+    let within_bounds = Arc::new(AtomicUsize::new(0));
+    let mut module = SpecModule::new(within_bounds.clone());
+    module.add_method(Settle).expect("method should register");
+
+    // Generated values out of their bounds are skipped, so every iteration must be within them
+    let report = fuzz_module(module, FuzzOptions::builder().iterations(50).build()).await;
+    report.assert_ok();
+    assert!(within_bounds.load(Ordering::SeqCst) >= 50);
+}
+
+#[test]
+#[should_panic(expected = "`get_user`: malformed error \"denied\": invalid type")]
+fn test_malformed_error_reported() {
+    // An error that can't be decoded is reported as such, not as accepted invalid params
+    let error = serde_json::json!("denied");
+    let message = serde_json::from_value::<jsonrpsee::types::ErrorObjectOwned>(error.clone())
+        .unwrap_err()
+        .to_string();
+    let report = FuzzReport {
+        calls: 1,
+        failures: vec![FuzzFailure {
+            method: "get_user".into(),
+            params: None,
+            kind: FuzzFailureKind::MalformedError { error, message },
+        }],
+    };
+    report.assert_ok();
+}
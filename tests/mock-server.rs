@@ -0,0 +1,144 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{MethodsError, http_client::HttpClientBuilder};
+use openspec_jsonrpsee::{MockServer, SpecModule, rpc, spec};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct User {
+    id: u32,
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Reading {
+    #[schemars(range(max = -5))]
+    below: i64,
+    #[schemars(extend("exclusiveMaximum" = -5))]
+    strictly_below: i64,
+    #[schemars(range(max = -40.5))]
+    temperature: f64,
+    #[schemars(extend("exclusiveMinimum" = 0, "exclusiveMaximum" = 1))]
+    ratio: f64,
+}
+
+#[rpc]
+fn read() -> Reading {
+    unimplemented!("only the spec is used by the mock")
+}
+
+#[rpc]
+fn get_user(_user_id: u32) -> User {
+    unimplemented!("only the spec is used by the mock")
+}
+
+#[rpc]
+fn list_users(_names: Vec<String>, _limit: Option<u32>) -> Vec<User> {
+    unimplemented!("only the spec is used by the mock")
+}
+
+/// Creates the spec the way a backend would, without ever serving the module.
+fn spec() -> Result<openspec_jsonrpsee::OpenRpcSpec, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?.add_method(ListUsers)?;
+
+    let mut spec = module.spec().clone();
+    spec.methods[0].examples = Some(vec![spec::ExamplePairing {
+        name: "Jane".into(),
+        description: None,
+        summary: None,
+        params: vec![spec::Example {
+            name: Some("_user_id".into()),
+            summary: None,
+            description: None,
+            value: Some(json!(2)),
+            external_value: None,
+        }],
+        result: Some(spec::Example {
+            name: None,
+            summary: None,
+            description: None,
+            value: Some(json!({ "id": 2, "name": "Jane", "email": "jane@example.com" })),
+            external_value: None,
+        }),
+    }]);
+    Ok(spec)
+}
+
+#[tokio::test]
+async fn test_mock_responses() -> Result<(), Box<dyn std::error::Error>> {
+    let (_handle, addr) = MockServer::from_spec(&spec()?)?
+        .start("127.0.0.1:0".parse()?)
+        .await?;
+    let client = HttpClientBuilder::default().build(format!("http://{addr}"))?;
+
+    // From the example
+    let user = GetUser::request(&client, 2).await?;
+    assert_eq!(user.name, "Jane");
+
+    // Synthesized from the result schema
+    let users = ListUsers::request(&client, vec!["a".into()], None).await?;
+    assert_eq!(
+        users,
+        vec![User {
+            id: 0,
+            name: "string".into(),
+            email: Some("string".into()),
+        }]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_validates_params() -> Result<(), Box<dyn std::error::Error>> {
+    let module = MockServer::from_spec(&spec()?)?.into_jsonrpsee_module();
+
+    let error = module
+        .call::<_, User>("get_user", ["not a number"])
+        .await
+        .expect_err("param should be invalid");
+    let MethodsError::JsonRpc(error) = error else {
+        panic!("expected a JSON-RPC error, got {error}")
+    };
+    assert_eq!(error.code(), -32602);
+
+    let (response, _) = module
+        .raw_json_request(
+            r#"{"jsonrpc":"2.0","id":1,"method":"list_users","params":{"names":[1],"_limit":5}}"#,
+            1,
+        )
+        .await?;
+    let response: serde_json::Value = serde_json::from_str(response.get())?;
+    assert_eq!(response["error"]["code"], -32602);
+    assert_eq!(
+        response["error"]["data"],
+        json!(["unknown param `names`", "missing required param `_names`"])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_samples_within_bounds() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Read)?;
+    let module = MockServer::from_spec(module.spec())?.into_jsonrpsee_module();
+
+    let reading: Reading = module.call("read", [(); 0]).await?;
+    assert_eq!(
+        reading,
+        Reading {
+            below: -5,
+            strictly_below: -6,
+            temperature: -40.5,
+            ratio: 0.5,
+        }
+    );
+
+    Ok(())
+}
@@ -16,8 +16,9 @@ async fn test_unit_ctx() {
         .add_method(UnitCtx)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = UnitCtx::request_unchecked(&client).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = UnitCtx::request_unchecked(client).await;
 
     assert_eq!("Response with unit context ()".to_string(), response);
 }
@@ -35,8 +36,9 @@ async fn test_simple_ctx() {
         .add_method(SimpleCtx)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = SimpleCtx::request_unchecked(&client).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = SimpleCtx::request_unchecked(client).await;
 
     assert_eq!(
         "Response with unit context \"string ctx\"".to_string(),
@@ -63,8 +65,9 @@ async fn test_struct_ctx() {
         .add_method(StructCtx)
         .expect("proof of concept should be able to register");
 
-    let (client, _addr) = test_server(module).await.expect("server should start");
-    let response = StructCtx::request_unchecked(&client).await;
+    let server = test_server(module).await.expect("server should start");
+    let client = server.client();
+    let response = StructCtx::request_unchecked(client).await;
 
     assert_eq!(
         "Response with unit context \"string ctx\"".to_string(),
@@ -0,0 +1,230 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use futures_util::stream;
+use jsonrpsee::{core::ClientError, http_client::HttpClient, server::Server};
+use openspec_jsonrpsee::{
+    LIMIT_EXCEEDED_CODE, Limits, RateLimit, RpcStream, SpecModule, rpc, spec, test_server,
+    test_server_ws,
+};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+#[rpc(rate_limit = "2/m")]
+fn lookup(id: u32) -> u32 {
+    id
+}
+
+#[rpc(max_concurrency = 1)]
+async fn register_user(name: String) -> String {
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    name
+}
+
+#[rpc(max_concurrency = 1)]
+fn watch_users() -> RpcStream<u32> {
+    RpcStream::new(stream::pending())
+}
+
+#[rpc]
+fn ping() -> String {
+    "pong".into()
+}
+
+fn method<'a>(module: &'a SpecModule, name: &str) -> &'a spec::Method {
+    module
+        .spec()
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .expect("method should be in the spec")
+}
+
+fn limit_exceeded_data(error: ClientError) -> Value {
+    match error {
+        ClientError::Call(error) => {
+            // Retried like a busy server, not mistaken for e.g. batches being unsupported
+            assert_eq!(error.code(), LIMIT_EXCEEDED_CODE);
+            assert_eq!(error.code(), jsonrpsee::types::error::SERVER_IS_BUSY_CODE);
+            serde_json::from_str(error.data().expect("data should be set").get()).unwrap()
+        }
+        error => panic!("expected a limit exceeded error, got {error:?}"),
+    }
+}
+
+#[test]
+fn test_rate_limit_parse() {
+    assert_eq!("100/s".parse(), Ok(RateLimit::per_second(100)));
+    assert_eq!("30/m".parse(), Ok(RateLimit::per_minute(30)));
+    assert_eq!("1000/h".parse(), Ok(RateLimit::per_hour(1000)));
+    assert_eq!(RateLimit::per_minute(30).to_string(), "30/m");
+    assert!("0/s".parse::<RateLimit>().is_err());
+    assert!("100/d".parse::<RateLimit>().is_err());
+    assert!("100".parse::<RateLimit>().is_err());
+}
+
+#[test]
+fn test_spec_records_limits() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Lookup)?.add_method(RegisterUser)?;
+    module.set_default_limits(
+        Limits::builder()
+            .rate_limit(RateLimit::per_second(100))
+            .build(),
+    );
+    module.add_method(Ping)?;
+
+    let lookup = method(&module, "lookup");
+    assert_eq!(
+        lookup.extensions.get(spec::extension::RATE_LIMIT),
+        Some(&json!("2/m"))
+    );
+    assert!(
+        !lookup
+            .extensions
+            .contains_key(spec::extension::MAX_CONCURRENCY)
+    );
+
+    // Each limit falls back to the default separately
+    let register_user = method(&module, "register_user");
+    assert_eq!(
+        Limits::of(register_user),
+        Limits::builder()
+            .rate_limit(RateLimit::per_second(100))
+            .max_concurrency(1)
+            .build()
+    );
+    assert_eq!(
+        register_user
+            .extensions
+            .get(spec::extension::MAX_CONCURRENCY),
+        Some(&json!(1))
+    );
+
+    let ping = method(&module, "ping");
+    assert_eq!(
+        ping.extensions.get(spec::extension::RATE_LIMIT),
+        Some(&json!("100/s"))
+    );
+
+    // Replacing the defaults keeps the limits of the methods themselves
+    module.set_default_limits(Limits::default());
+    assert!(Limits::of(method(&module, "ping")).is_empty());
+    assert_eq!(
+        Limits::of(method(&module, "register_user")),
+        Limits::builder().max_concurrency(1).build()
+    );
+    assert_eq!(
+        Limits::of(method(&module, "lookup")),
+        Limits::builder()
+            .rate_limit(RateLimit::per_minute(2))
+            .build()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Lookup)?.add_method(Ping)?;
+
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    assert_eq!(Lookup::request(client, 1).await?, 1);
+    assert_eq!(Lookup::request(client, 2).await?, 2);
+    let data = limit_exceeded_data(Lookup::request(client, 3).await.unwrap_err());
+    assert_eq!(data["limit"], "rateLimit");
+    assert_eq!(data["rateLimit"], "2/m");
+    let retry_after = data["retryAfterMs"]
+        .as_u64()
+        .expect("retry after should be set");
+    assert!(retry_after > 0 && retry_after <= 30_000, "{retry_after}");
+
+    // Other methods aren't limited
+    for _ in 0..5 {
+        assert_eq!(Ping::request(client).await?, "pong");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrency() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(RegisterUser)?;
+
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    let (first, second) = tokio::join!(RegisterUser::request(client, "Jane".into()), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        RegisterUser::request(client, "John".into()).await
+    });
+    assert_eq!(first?, "Jane");
+    let data = limit_exceeded_data(second.unwrap_err());
+    assert_eq!(
+        data,
+        json!({ "limit": "maxConcurrency", "maxConcurrency": 1 })
+    );
+
+    // The permit is released once the call is handled
+    assert_eq!(RegisterUser::request(client, "John".into()).await?, "John");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrency_subscriptions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(WatchUsers)?;
+
+    let server = test_server_ws(module).await?;
+    let client = server.client();
+
+    // An open subscription counts as running
+    let watching = WatchUsers::subscribe(client).await?;
+    let data = limit_exceeded_data(WatchUsers::subscribe(client).await.unwrap_err());
+    assert_eq!(data["limit"], "maxConcurrency");
+
+    // The permit is released once the subscription is closed
+    watching.unsubscribe().await?;
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while WatchUsers::subscribe(client).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_limits_without_middleware() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(Lookup)?.add_method(Ping)?;
+    // Default limits apply to the methods added before them
+    module.set_default_limits(
+        Limits::builder()
+            .rate_limit(RateLimit::per_minute(1))
+            .build(),
+    );
+
+    let server = Server::builder().build("127.0.0.1:0").await?;
+    let addr = server.local_addr()?;
+    let handle = server.start(module.into_jsonrpsee_module());
+    let client = HttpClient::builder().build(format!("http://{addr}"))?;
+
+    assert_eq!(Lookup::request(&client, 1).await?, 1);
+    assert_eq!(Lookup::request(&client, 2).await?, 2);
+    let data = limit_exceeded_data(Lookup::request(&client, 3).await.unwrap_err());
+    assert_eq!(data["rateLimit"], "2/m");
+
+    assert_eq!(Ping::request(&client).await?, "pong");
+    let data = limit_exceeded_data(Ping::request(&client).await.unwrap_err());
+    assert_eq!(data["rateLimit"], "1/m");
+
+    handle.stop()?;
+    Ok(())
+}
@@ -0,0 +1,269 @@
+#![allow(deprecated)]
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{
+    core::ClientError,
+    http_client::HttpClient,
+    server::{
+        Server, ServerConfig,
+        middleware::rpc::{
+            Batch, MethodResponse, Notification, Request, RpcServiceBuilder, RpcServiceT,
+        },
+    },
+    types::ErrorObject,
+};
+use openspec_jsonrpsee::{
+    MethodMetadata, SpecModule, batch_request, rpc, test_in_process, test_server,
+};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+/// Records the methods (and whether they're deprecated) it sees, under a label.
+#[derive(Clone)]
+struct SpyLayer {
+    label: &'static str,
+    seen: Arc<Mutex<Vec<String>>>,
+}
+
+impl<S> tower::Layer<S> for SpyLayer {
+    type Service = Spy<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Spy {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Spy<S> {
+    inner: S,
+    layer: SpyLayer,
+}
+
+impl<S> RpcServiceT for Spy<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let method = MethodMetadata::get(request.extensions()).expect("metadata should be set");
+        self.layer.seen.lock().unwrap().push(format!(
+            "{}:{}:{}",
+            self.layer.label,
+            method.name,
+            method.deprecated.unwrap_or_default()
+        ));
+        self.inner.call(request)
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.inner.batch(batch)
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        self.inner.notification(notification)
+    }
+}
+
+/// Rejects every call, without calling the method.
+#[derive(Clone)]
+struct Deny;
+
+impl<S> tower::Layer<S> for Deny {
+    type Service = Denied;
+
+    fn layer(&self, _inner: S) -> Self::Service {
+        Denied
+    }
+}
+
+#[derive(Clone)]
+struct Denied;
+
+impl RpcServiceT for Denied {
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+    type BatchResponse = MethodResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let error = ErrorObject::owned(-32001, "Denied", None::<()>);
+        std::future::ready(MethodResponse::error(request.id, error))
+    }
+
+    fn batch<'a>(&self, _batch: Batch<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        std::future::ready(MethodResponse::notification())
+    }
+
+    fn notification<'a>(
+        &self,
+        _notification: Notification<'a>,
+    ) -> impl Future<Output = MethodResponse> + Send + 'a {
+        std::future::ready(MethodResponse::notification())
+    }
+}
+
+#[rpc(tags = ["admin"])]
+#[deprecated]
+fn delete_user(id: u32) -> u32 {
+    id
+}
+
+#[rpc(tags = ["users"])]
+fn get_user(id: u32) -> u32 {
+    id
+}
+
+#[rpc(middleware = Deny)]
+fn forbidden() -> String {
+    unreachable!("the middleware should reject every call")
+}
+
+#[tokio::test]
+async fn test_scoped_middleware() -> Result<(), Box<dyn std::error::Error>> {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let spy = |label| SpyLayer {
+        label,
+        seen: seen.clone(),
+    };
+
+    let mut module = SpecModule::new(());
+    module
+        .layer_for_tag("admin", spy("admin-outer"))
+        .layer_for_tag("admin", spy("admin-inner"));
+    module
+        .add_method(DeleteUser)?
+        .add_method(GetUser)?
+        .add_method(Forbidden)?;
+    module.layer_for_method("get_user", spy("get_user"));
+
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    assert_eq!(DeleteUser::request(client, 1).await?, 1);
+    assert_eq!(GetUser::request(client, 2).await?, 2);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            "admin-outer:delete_user:true",
+            "admin-inner:delete_user:true",
+            "get_user:get_user:false",
+        ]
+    );
+
+    let error = Forbidden::request(client)
+        .await
+        .expect_err("should be denied");
+    let ClientError::Call(error) = error else {
+        panic!("expected a call error, got {error}")
+    };
+    assert_eq!(error.code(), -32001);
+
+    // Batched calls go through the layers of their own method too
+    seen.lock().unwrap().clear();
+    let (deleted, forbidden) =
+        batch_request(client, (DeleteUser::batch_item(3), Forbidden::batch_item())).await?;
+    assert_eq!(deleted?, 3);
+    assert!(forbidden.is_err());
+    assert_eq!(seen.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_process_middleware() -> Result<(), Box<dyn std::error::Error>> {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?.add_method(Forbidden)?;
+    module.layer_for_method(
+        "get_user",
+        SpyLayer {
+            label: "get_user",
+            seen: seen.clone(),
+        },
+    );
+
+    let client = test_in_process(module);
+
+    assert_eq!(GetUser::request(&client, 2).await?, 2);
+    assert_eq!(*seen.lock().unwrap(), vec!["get_user:get_user:false"]);
+    let error = Forbidden::request(&client)
+        .await
+        .expect_err("should be denied");
+    let ClientError::Call(error) = error else {
+        panic!("expected a call error, got {error}")
+    };
+    assert_eq!(error.code(), -32001);
+
+    // Batches are sent as such, each call going through the layers of its method
+    seen.lock().unwrap().clear();
+    let (user, forbidden, other) = batch_request(
+        &client,
+        (
+            GetUser::batch_item(3),
+            Forbidden::batch_item(),
+            GetUser::batch_item(4),
+        ),
+    )
+    .await?;
+    assert_eq!(user?, 3);
+    let ClientError::Call(error) = forbidden.expect_err("should be denied") else {
+        panic!("expected a call error")
+    };
+    assert_eq!(error.code(), -32001);
+    assert_eq!(other?, 4);
+    assert_eq!(seen.lock().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_max_response_body_size() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?;
+
+    // A single response (`{"jsonrpc":"2.0","id":0,"result":1}`) fits, but not two
+    let max_response_body_size = 64;
+    let server = Server::builder()
+        .set_config(
+            ServerConfig::builder()
+                .max_response_body_size(max_response_body_size)
+                .build(),
+        )
+        .set_rpc_middleware(
+            RpcServiceBuilder::new().layer(
+                module
+                    .rpc_middleware()
+                    .max_response_body_size(max_response_body_size),
+            ),
+        )
+        .build("127.0.0.1:0")
+        .await?;
+    let addr = server.local_addr()?;
+    let handle = server.start(module.into_jsonrpsee_module());
+    let client = HttpClient::builder().build(format!("http://{addr}"))?;
+
+    let (user,) = batch_request(&client, (GetUser::batch_item(1),)).await?;
+    assert_eq!(user?, 1);
+    batch_request(&client, (GetUser::batch_item(1), GetUser::batch_item(2)))
+        .await
+        .expect_err("the response should be too big");
+
+    handle.stop()?;
+    Ok(())
+}
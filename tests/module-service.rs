@@ -0,0 +1,172 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{SpecModule, rpc, test::test_server};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+struct UserService {
+    users: Mutex<Vec<String>>,
+}
+
+pub struct UserClient {
+    client: jsonrpsee::http_client::HttpClient,
+}
+
+#[rpc]
+impl UserService {
+    /// Registers a user, returning their id
+    #[rpc(client = UserClient)]
+    fn register_user(&self, name: String) -> usize {
+        let mut users = self.users.lock().unwrap();
+        users.push(name);
+        users.len() - 1
+    }
+
+    #[rpc(tags = ["read"], client = UserClient)]
+    async fn get_user(&self, id: usize) -> Option<String> {
+        self.users.lock().unwrap().get(id).cloned()
+    }
+
+    #[rpc(blocking)]
+    fn count_users(&self) -> usize {
+        self.len()
+    }
+
+    // Not marked, so it's a plain method of the service
+    fn len(&self) -> usize {
+        self.users.lock().unwrap().len()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct TeamService {
+    teams: Vec<String>,
+}
+
+#[rpc]
+impl TeamService {
+    // Same name as the method of `UserService`, the structs being prefixed with their service
+    #[rpc]
+    fn count_users(&self) -> usize {
+        self.teams.len()
+    }
+
+    /// Returns the whole service, `Self` being the type of the service
+    #[rpc]
+    fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    #[rpc]
+    fn merge(&self, others: Vec<Self>) -> Option<Self> {
+        let mut merged = self.clone();
+        merged
+            .teams
+            .extend(others.into_iter().flat_map(|other| other.teams));
+        Some(merged)
+    }
+}
+
+fn service() -> UserService {
+    UserService {
+        users: Mutex::new(vec!["Jane".into()]),
+    }
+}
+
+#[test]
+fn test_service_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let module = SpecModule::from_service(service())?;
+    let methods = &module.spec().methods;
+
+    let names: Vec<_> = methods.iter().map(|method| method.name.as_str()).collect();
+    assert_eq!(names, ["register_user", "get_user", "count_users"]);
+
+    // `&self` isn't a param
+    let params: Vec<_> = methods[0]
+        .params
+        .iter()
+        .map(|param| param.name.as_str())
+        .collect();
+    assert_eq!(params, ["name"]);
+    assert_eq!(
+        methods[0].summary.as_deref(),
+        Some("Registers a user, returning their id")
+    );
+    assert_eq!(methods[1].tag_names(), ["read"]);
+    assert!(methods[2].params.is_empty());
+
+    // Unmarked methods are left as is
+    assert_eq!(service().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let module = SpecModule::from_service(service())?;
+    let server = test_server(module).await?;
+    let client = server.client();
+
+    assert_eq!(
+        UserServiceRegisterUser::request(client, "John".into()).await?,
+        1
+    );
+    assert_eq!(
+        UserServiceGetUser::request(client, 1).await?,
+        Some("John".into())
+    );
+    assert_eq!(UserServiceCountUsers::request(client).await?, 2);
+
+    let user_client = UserClient {
+        client: client.clone(),
+    };
+    assert_eq!(user_client.register_user("Alice".into()).await?, 2);
+    assert_eq!(user_client.get_user(0).await?, Some("Jane".into()));
+    assert_eq!(user_client.get_user(7).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_services_sharing_method_names() -> Result<(), Box<dyn std::error::Error>> {
+    let teams = TeamService {
+        teams: vec!["core".into()],
+    };
+    let module = SpecModule::from_service(teams)?;
+    let method = |name: &str| {
+        module
+            .spec()
+            .methods
+            .iter()
+            .find(|method| method.name == name)
+            .cloned()
+            .expect("method should be in the spec")
+    };
+    // Named after the method, not the struct
+    assert_eq!(
+        method("count_users").result.unwrap().name,
+        "CountUsersResponse"
+    );
+    let snapshot = serde_json::to_value(method("snapshot").result.unwrap().schema)?;
+    assert_eq!(snapshot["properties"]["teams"]["type"], "array");
+
+    let server = test_server(module).await?;
+    let client = server.client();
+    assert_eq!(TeamServiceCountUsers::request(client).await?, 1);
+    assert_eq!(TeamServiceSnapshot::request(client).await?.teams, ["core"]);
+    let others = vec![TeamService {
+        teams: vec!["web".into()],
+    }];
+    let merged = TeamServiceMerge::request(client, others).await?;
+    assert_eq!(
+        merged.map(|merged| merged.teams),
+        Some(vec!["core".into(), "web".into()])
+    );
+
+    let users = test_server(SpecModule::from_service(service())?).await?;
+    assert_eq!(UserServiceCountUsers::request(users.client()).await?, 1);
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 #![allow(deprecated)]
 
-use openspec_jsonrpsee::{SpecModule, rpc};
+use openspec_jsonrpsee::{SpecModule, assert_spec_snapshot, rpc, spec};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 struct StructA {
@@ -39,30 +40,139 @@ async fn test_generated_spec() -> Result<(), Box<dyn std::error::Error>> {
     let a = spec.methods[0].clone();
     assert_eq!(a.name, "method_a");
     assert_eq!(a.deprecated, Some(false));
-    assert_eq!(a.description, Some("This is a method description".into()));
+    assert_eq!(a.summary, Some("This is a method description".into()));
+    assert_eq!(a.description, None);
     assert!(a.servers.is_none());
     assert!(a.errors.is_none());
     assert_eq!(a.params.len(), 1);
     assert_eq!(a.params[0].name, "value");
     assert_eq!(a.params[0].description, None);
     assert_eq!(a.params[0].required, Some(true));
-    assert_eq!(a.params[0].schema, schemars::schema_for!(StructA));
-    let a_result = a.result.clone().expect("method a should have spec result");
-    assert_eq!(a_result.schema, schemars::schema_for!(StructB));
+    assert!(a.result.is_some());
 
     let b = spec.methods[1].clone();
     assert_eq!(b.name, "method_b");
     assert_eq!(b.deprecated, Some(true));
-    assert_eq!(b.description, None);
+    assert_eq!(b.summary, None);
+    assert_eq!(b.description, Some("**Deprecated**".into()));
+    assert_eq!(b.replaced_by(), None);
     assert!(b.servers.is_none());
     assert!(b.errors.is_none());
     assert_eq!(b.params.len(), 1);
     assert_eq!(b.params[0].name, "value");
     assert_eq!(b.params[0].description, None);
     assert_eq!(b.params[0].required, Some(true));
-    assert_eq!(b.params[0].schema, schemars::schema_for!(StructB));
-    let b_result = b.result.clone().expect("method b should have spec result");
-    assert_eq!(b_result.schema, schemars::schema_for!(String));
+    assert!(b.result.is_some());
+
+    // The schemas (and everything else) are compared against the committed contract
+    assert_spec_snapshot!(module, "tests/snapshots/module-spec.json");
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(
+    expected = "(- snapshot, + actual), run with OPENSPEC_UPDATE=1 to update it:\n- /methods/1: {\"deprecated\":true"
+)]
+fn test_snapshot_mismatch() {
+    // Missing `method_b` of the snapshot, listed by its pointer
+    let mut module = SpecModule::new(());
+    module.add_method(MethodA).expect("method should register");
+    assert_spec_snapshot!(module, "tests/snapshots/module-spec.json");
+}
+
+#[rpc(tags = ["users", "admin"], external_docs = "https://example.com/docs/method_c")]
+/// Fetches the user.
+///
+/// The user is looked up by the name of the given value.
+fn method_c(value: StructB) -> String {
+    value.name
+}
+
+#[rpc(tags = ["users"], summary = "Explicit summary")]
+/// Whole doc comment.
+///
+/// Becomes the description.
+fn method_d(value: StructB) -> String {
+    value.name
+}
+
+#[tokio::test]
+async fn test_generated_spec_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(MethodC)?;
+    module.add_spec_tag(
+        spec::Tag::builder()
+            .name("users".into())
+            .summary("User management".into())
+            .build(),
+    );
+    module.add_method(MethodD)?;
+    let spec = module.spec();
+
+    let c = spec.methods[0].clone();
+    assert_eq!(c.summary, Some("Fetches the user.".into()));
+    assert_eq!(
+        c.description,
+        Some("The user is looked up by the name of the given value.".into())
+    );
+    assert_eq!(
+        c.external_docs.as_ref().map(|docs| docs.url.as_str()),
+        Some("https://example.com/docs/method_c")
+    );
+    assert_eq!(c.tag_names(), vec!["users", "admin"]);
+    let c_tags = serde_json::to_value(&c.tags)?;
+    assert_eq!(
+        c_tags,
+        json!([{ "$ref": "#/components/tags/users" }, { "name": "admin" }])
+    );
+
+    let d = spec.methods[1].clone();
+    assert_eq!(d.summary, Some("Explicit summary".into()));
+    assert_eq!(
+        d.description,
+        Some("Whole doc comment.\n\nBecomes the description.".into())
+    );
+    let d_tags = serde_json::to_value(&d.tags)?;
+    assert_eq!(d_tags, json!([{ "$ref": "#/components/tags/users" }]));
+
+    let tags = spec
+        .components
+        .as_ref()
+        .and_then(|components| components.tags.as_ref())
+        .expect("declared tags should be in components");
+    assert_eq!(tags["users"].summary, Some("User management".into()));
+
+    Ok(())
+}
+
+#[rpc(replaced_by = "method_a")]
+/// Gets the name of B.
+#[deprecated(since = "0.2.0", note = "B is no longer stored by name")]
+fn method_e(value: StructB) -> String {
+    value.name
+}
+
+#[tokio::test]
+async fn test_generated_spec_deprecation() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    let spec = module.add_method(MethodE)?.spec();
+
+    let e = spec.methods[0].clone();
+    assert_eq!(e.deprecated, Some(true));
+    assert_eq!(e.summary, Some("Gets the name of B.".into()));
+    assert_eq!(
+        e.description,
+        Some(
+            "**Deprecated** since 0.2.0: B is no longer stored by name (replaced by `method_a`)"
+                .into()
+        )
+    );
+    assert_eq!(e.replaced_by(), Some("method_a"));
+    assert_eq!(
+        serde_json::to_value(&e)?["x-replaced-by"],
+        json!("method_a")
+    );
 
     Ok(())
 }
@@ -0,0 +1,223 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::core::RpcResult;
+use openspec_jsonrpsee::{
+    DiagnosticKind, Page, PageRequest, RpcMethod, ServerHandler, SpecModule, assert_spec_valid,
+    rpc, spec,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[rpc]
+fn get_user(id: u32) -> User {
+    User {
+        id,
+        name: "Jane".into(),
+    }
+}
+
+#[rpc(paginated)]
+fn list_users(page: PageRequest) -> Page<User> {
+    let _ = page;
+    Page::last(vec![])
+}
+
+#[rpc]
+fn users_get(id: u32) -> User {
+    get_user(id)
+}
+
+#[rpc]
+fn reset(_unit: ()) -> bool {
+    true
+}
+
+trait Entity:
+    JsonSchema + Serialize + Clone + for<'de> Deserialize<'de> + Default + Send + 'static
+{
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+struct Order {
+    id: u32,
+}
+
+impl Entity for User {}
+impl Entity for Order {}
+
+#[rpc]
+fn get_entity<T: Entity>(_id: u32) -> T {
+    T::default()
+}
+
+/// A method with the given specification, e.g. edited after its generation
+struct WithSpec(spec::Method);
+
+impl RpcMethod<(), bool> for WithSpec {
+    fn name(&self) -> &'static str {
+        "get_user"
+    }
+
+    fn spec(&self) -> spec::Method {
+        self.0.clone()
+    }
+
+    fn handler(&self) -> ServerHandler<(), RpcResult<bool>> {
+        ServerHandler::Sync(|_, _, _| Ok(true))
+    }
+}
+
+fn info() -> spec::Info {
+    spec::Info::builder()
+        .title("Users".into())
+        .version("1.0.0".into())
+        .build()
+}
+
+fn server(url: &str, variables: &[&str]) -> spec::Server {
+    let variables: HashMap<String, spec::ServerVariable> = variables
+        .iter()
+        .map(|name| {
+            let variable = spec::ServerVariable {
+                r#enum: None,
+                default: "api".into(),
+                description: None,
+            };
+            (name.to_string(), variable)
+        })
+        .collect();
+    spec::Server::builder()
+        .name("production".into())
+        .url(url.into())
+        .variables(variables)
+        .build()
+}
+
+fn diagnostics(module: &SpecModule) -> Vec<(DiagnosticKind, String)> {
+    module
+        .validate_spec()
+        .unwrap_err()
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.pointer))
+        .collect()
+}
+
+#[test]
+fn test_valid_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?.add_method(ListUsers)?;
+    module.set_spec_info(info());
+    module.set_spec_servers(vec![server(
+        "https://{subdomain}.example.com",
+        &["subdomain"],
+    )]);
+
+    module.validate_spec()?;
+    assert_spec_valid(&module);
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_info() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?;
+    assert_eq!(
+        diagnostics(&module),
+        [(DiagnosticKind::MissingInfo, "/info".into())]
+    );
+
+    module.set_spec_info(spec::Info::builder().title(" ".into()).build());
+    assert_eq!(
+        diagnostics(&module),
+        [(DiagnosticKind::MissingInfo, "/info".into())]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generation_diagnostics() -> Result<(), Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module
+        .add_method(Reset)?
+        .add_method(GetEntity::<User>::named("get_user"))?
+        .add_method(GetEntity::<Order>::named("get_order"))?
+        // Both results are named `UsersGetResponse`
+        .add_method(UsersGet)?
+        .add_method(GetEntity::<Order>::named("users.get"))?;
+    module.set_spec_info(info());
+    module.set_spec_servers(vec![server(
+        "https://{subdomain}.example.com/{version}",
+        &["subdomain"],
+    )]);
+
+    assert_eq!(
+        diagnostics(&module),
+        [
+            (
+                DiagnosticKind::UnresolvedServerVariable,
+                "/servers/0/url".into()
+            ),
+            (DiagnosticKind::UnitParam, "/methods/0/params/0".into()),
+            (
+                DiagnosticKind::DuplicateResultName,
+                "/methods/4/result/name".into()
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    // An example of value `null` is read back without a value
+    let mut method = RpcMethod::<(), User>::spec(&GetUser);
+    method.examples = Some(vec![spec::ExamplePairing {
+        name: "no user".into(),
+        description: None,
+        summary: None,
+        params: vec![],
+        result: Some(spec::Example {
+            name: None,
+            summary: None,
+            description: None,
+            value: Some(json!(null)),
+            external_value: None,
+        }),
+    }]);
+    let mut module = SpecModule::new(());
+    module.add_method(WithSpec(method))?;
+    module.set_spec_info(info());
+
+    let error = module.validate_spec().unwrap_err();
+    let round_trip = error
+        .diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.kind == DiagnosticKind::RoundTrip)
+        .expect("the method shouldn't read back");
+    assert_eq!(round_trip.pointer, "/methods/0/examples/0/result/value");
+    assert!(round_trip.message.contains("doesn't read back"));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "/methods/0/params/0: the param `_unit` of `reset` is of type `()`")]
+fn test_assert_spec_valid() {
+    let mut module = SpecModule::new(());
+    module.add_method(Reset).unwrap();
+    module.set_spec_info(info());
+    assert_spec_valid(&module);
+}
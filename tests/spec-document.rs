@@ -0,0 +1,250 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{
+    DiagnosticKind, OpenRpcSpec, Page, PageRequest, SpecLoadError, SpecModule, rpc,
+    validate_document,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct Address {
+    city: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct User {
+    id: u32,
+    address: Address,
+    friends: Vec<User>,
+}
+
+#[rpc]
+fn get_user(_id: u32) -> User {
+    unimplemented!("only the spec is used")
+}
+
+#[rpc(paginated)]
+fn list_users(_city: Option<String>, _page: PageRequest) -> Page<User> {
+    unimplemented!("only the spec is used")
+}
+
+fn spec() -> Result<OpenRpcSpec, Box<dyn std::error::Error>> {
+    let mut module = SpecModule::new(());
+    module.add_method(GetUser)?.add_method(ListUsers)?;
+    Ok(module.spec().clone())
+}
+
+/// Writes the contents to a file of the temporary directory, unique to the test
+fn temp_file(name: &str, contents: &str) -> std::io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("openspec-{}-{name}", std::process::id()));
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+fn kinds(document: serde_json::Value) -> Vec<(DiagnosticKind, String)> {
+    validate_document(&document)
+        .unwrap_err()
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.pointer))
+        .collect()
+}
+
+#[test]
+fn test_generated_spec_is_valid() -> Result<(), Box<dyn std::error::Error>> {
+    let spec = spec()?;
+    spec.validate()?;
+
+    // Also once written and read back
+    let spec: OpenRpcSpec = spec.to_string_pretty().parse()?;
+    spec.validate()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_load_from_path() -> Result<(), Box<dyn std::error::Error>> {
+    let json = temp_file("spec.json", &spec()?.to_string_pretty())?;
+    let spec = OpenRpcSpec::from_path(&json)?;
+    assert_eq!(spec.methods.len(), 2);
+    assert_eq!(spec.methods[0].name, "get_user");
+
+    let yaml = temp_file(
+        "spec.yaml",
+        r##"
+openrpc: 1.3.2
+info:
+  title: Users
+  version: 1.0.0
+methods:
+  - name: get_user
+    params:
+      - name: id
+        required: true
+        schema:
+          type: integer
+    result:
+      name: user
+      schema:
+        $ref: "#/components/schemas/User"
+components:
+  schemas:
+    User:
+      type: object
+"##,
+    )?;
+    let spec = OpenRpcSpec::from_path(&yaml)?;
+    assert_eq!(spec.info.title, "Users");
+    assert_eq!(spec.methods[0].params[0].name, "id");
+    spec.validate()?;
+
+    let missing = OpenRpcSpec::from_path(std::env::temp_dir().join("openspec-missing.json"));
+    assert!(matches!(missing, Err(SpecLoadError::Io(_))));
+    let invalid: Result<OpenRpcSpec, _> = "{ \"methods\": 1 }".parse();
+    assert!(matches!(invalid, Err(SpecLoadError::Json(_))));
+
+    // JSON is read whatever the extension, even after a byte order mark
+    let bom = temp_file("spec", &format!("\u{feff}{}", spec.to_string_pretty()))?;
+    assert_eq!(OpenRpcSpec::from_path(&bom)?.methods.len(), 1);
+    let invalid = temp_file("spec-invalid.json", "\u{feff}{ \"methods\": 1 }")?;
+    assert!(matches!(
+        OpenRpcSpec::from_path(&invalid),
+        Err(SpecLoadError::Json(_))
+    ));
+
+    // Declared whatever the features, here with `yaml`, so matching it never breaks
+    assert!(!matches!(
+        OpenRpcSpec::from_path(&yaml),
+        Err(SpecLoadError::YamlDisabled)
+    ));
+    assert_eq!(
+        SpecLoadError::YamlDisabled.to_string(),
+        "YAML documents require the `yaml` feature"
+    );
+
+    std::fs::remove_file(json)?;
+    std::fs::remove_file(yaml)?;
+    std::fs::remove_file(bom)?;
+    std::fs::remove_file(invalid)?;
+    Ok(())
+}
+
+#[test]
+fn test_extensions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut method = spec()?.methods.remove(0);
+    method.extensions.insert("x-team".into(), json!("users"));
+    // Not an extension, so it can't overwrite the field
+    method
+        .extensions
+        .insert("name".into(), json!("delete_user"));
+
+    let written = serde_json::to_value(&method)?;
+    assert_eq!(written["name"], "get_user");
+    assert_eq!(written["x-team"], "users");
+
+    // Only the `x-` fields left are read as extensions
+    let mut document = written;
+    document["unknown"] = json!(true);
+    let read: openspec_jsonrpsee::spec::Method = serde_json::from_value(document)?;
+    assert_eq!(read.extensions.len(), 1);
+    assert_eq!(read.extensions["x-team"], "users");
+
+    Ok(())
+}
+
+#[test]
+fn test_meta_schema_diagnostics() {
+    let diagnostics = kinds(json!({
+        "openrpc": "2.0.0",
+        "info": { "title": "Users" },
+        "methods": [{ "name": "get_user", "params": [], "unknown": true }],
+    }));
+    assert!(
+        diagnostics
+            .iter()
+            .all(|(kind, _)| *kind == DiagnosticKind::MetaSchema)
+    );
+    let pointers: Vec<&str> = diagnostics
+        .iter()
+        .map(|(_, pointer)| pointer.as_str())
+        .collect();
+    assert!(pointers.contains(&"/openrpc"), "{pointers:?}");
+    assert!(pointers.contains(&"/info"), "{pointers:?}");
+    assert!(pointers.contains(&"/methods/0"), "{pointers:?}");
+}
+
+#[test]
+fn test_semantic_diagnostics() {
+    let integer = json!({ "type": "integer" });
+    let diagnostics = kinds(json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "Users", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "get_user",
+                "paramStructure": "by-position",
+                "params": [
+                    { "name": "id", "schema": integer },
+                    { "name": "id", "required": true, "schema": integer },
+                ],
+                "result": { "name": "user", "schema": { "$ref": "#/$defs/User" } },
+                "links": [{ "name": "friends", "method": "list_friends" }],
+            },
+            {
+                "name": "get_user",
+                "params": [],
+                "result": { "name": "user", "schema": { "$ref": "#/components/schemas/User" } },
+            },
+        ],
+    }));
+    assert_eq!(
+        diagnostics,
+        [
+            (
+                DiagnosticKind::DuplicateParamName,
+                "/methods/0/params/1".into()
+            ),
+            (
+                DiagnosticKind::RequiredParamAfterOptional,
+                "/methods/0/params/1".into()
+            ),
+            (
+                DiagnosticKind::DuplicateMethodName,
+                "/methods/1/name".into()
+            ),
+            (
+                DiagnosticKind::UnresolvedReference,
+                "/methods/0/result/schema".into()
+            ),
+            (
+                DiagnosticKind::UnresolvedReference,
+                "/methods/1/result/schema".into()
+            ),
+            (
+                DiagnosticKind::UnknownLinkMethod,
+                "/methods/0/links/0/method".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_diagnostics_display() {
+    let error = validate_document(&json!({
+        "openrpc": "1.3.2",
+        "info": { "title": "Users", "version": "1.0.0" },
+        "methods": [
+            { "name": "ping", "params": [] },
+            { "name": "ping", "params": [] },
+        ],
+    }))
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid OpenRPC document:\n- /methods/1/name: the method name `ping` is not unique"
+    );
+}
@@ -0,0 +1,43 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use openspec_jsonrpsee::{SNAPSHOT_UPDATE_VAR, SpecModule, assert_spec_snapshot_at, rpc};
+
+#[rpc]
+fn get_version() -> String {
+    env!("CARGO_PKG_VERSION").into()
+}
+
+#[rpc]
+fn get_name() -> String {
+    env!("CARGO_PKG_NAME").into()
+}
+
+// The only test of this file, as it sets the environment variable read by every snapshot
+#[test]
+fn test_snapshot_update() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("openspec-snapshot-{}", std::process::id()));
+    let path = dir.join("nested/api.json");
+    let mut module = SpecModule::new(());
+    module.add_method(GetVersion)?;
+
+    // Created, along with its directory
+    // SAFETY: no other test of this file reads or writes the environment
+    unsafe { std::env::set_var(SNAPSHOT_UPDATE_VAR, "1") };
+    assert_spec_snapshot_at(module.spec(), &path);
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    assert_eq!(written, serde_json::to_value(module.spec())?);
+
+    // Rewritten once the spec changes
+    module.add_method(GetName)?;
+    assert_spec_snapshot_at(module.spec(), &path);
+    unsafe { std::env::remove_var(SNAPSHOT_UPDATE_VAR) };
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    assert_eq!(written["methods"][1]["name"], "get_name");
+
+    // Asserted against once not updating
+    assert_spec_snapshot_at(module.spec(), &path);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::{
+    core::{ClientError, client::ClientT},
+    rpc_params,
+};
+use openspec_jsonrpsee::{
+    SpecModule, batch_request, rpc, test_in_process, test_server, test_server_ws,
+};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
+
+#[rpc]
+/// Greets someone by name.
+fn greet(#[context] _ctx: &Arc<Notify>, name: String) -> String {
+    format!("Hello {name}")
+}
+
+#[rpc(notification)]
+/// Fire-and-forget method, the caller never waits for it to run.
+fn ping(#[context] ctx: &Arc<Notify>) {
+    ctx.notify_one();
+}
+
+/// Released by the test, to respond after the calls made meanwhile
+static RELEASE: Notify = Notify::const_new();
+
+#[rpc]
+async fn wait_for_release(#[context] _ctx: &Arc<Notify>) -> bool {
+    RELEASE.notified().await;
+    true
+}
+
+fn module(pinged: Arc<Notify>) -> SpecModule<Arc<Notify>> {
+    let mut module = SpecModule::new(pinged);
+    module
+        .add_method(Greet)
+        .expect("method should register")
+        .add_method(Ping)
+        .expect("method should register")
+        .add_method(WaitForRelease)
+        .expect("method should register");
+    module
+}
+
+#[tokio::test]
+async fn test_ws_client() -> Result<(), Box<dyn std::error::Error>> {
+    let pinged = Arc::new(Notify::new());
+    let server = test_server_ws(module(pinged.clone())).await?;
+    let client = server.client();
+
+    assert_eq!(Greet::request(client, "Jane".into()).await?, "Hello Jane");
+
+    Ping::notify(client).await?;
+    tokio::time::timeout(Duration::from_secs(1), pinged.notified()).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_process_client() -> Result<(), Box<dyn std::error::Error>> {
+    let pinged = Arc::new(Notify::new());
+    let client = test_in_process(module(pinged.clone()));
+
+    assert_eq!(Greet::request(&client, "Jane".into()).await?, "Hello Jane");
+
+    Ping::notify(&client).await?;
+    tokio::time::timeout(Duration::from_secs(1), pinged.notified()).await?;
+
+    let (first, second) = batch_request(
+        &client,
+        (Greet::batch_item("A".into()), Greet::batch_item("B".into())),
+    )
+    .await?;
+    assert_eq!(first?, "Hello A");
+    assert_eq!(second?, "Hello B");
+
+    // Errors are returned just like by a server
+    let error = client
+        .request::<String, _>("missing", rpc_params![])
+        .await
+        .expect_err("method shouldn't exist");
+    let ClientError::Call(error) = error else {
+        panic!("expected a call error, got {error}")
+    };
+    assert_eq!(error.code(), -32601);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_records_and_stops() -> Result<(), Box<dyn std::error::Error>> {
+    let pinged = Arc::new(Notify::new());
+    let server = test_server(module(pinged.clone())).await?;
+    let client = server.client();
+
+    Greet::request(client, "Jane".into()).await?;
+    Ping::notify(client).await?;
+    tokio::time::timeout(Duration::from_secs(1), pinged.notified()).await?;
+
+    let calls = server.recorded_calls();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].request["method"], "greet");
+    assert_eq!(calls[0].request["params"], json!(["Jane"]));
+    assert_eq!(calls[0].response.as_ref().unwrap()["result"], "Hello Jane");
+    assert_eq!(calls[1].request["method"], "ping");
+    assert_eq!(calls[1].response, None);
+
+    server.stop().await?;
+    assert!(server.is_stopped());
+    assert!(server.stop().await.is_err());
+    Greet::request(client, "Jane".into())
+        .await
+        .expect_err("server should be stopped");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_records_in_received_order() -> Result<(), Box<dyn std::error::Error>> {
+    let server = test_server(module(Arc::new(Notify::new()))).await?;
+    let client = server.client().clone();
+
+    // Received first, but responded to last
+    let waiting = tokio::spawn(async move { WaitForRelease::request(&client).await });
+    while server.recorded_calls().is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert_eq!(server.recorded_calls()[0].response, None);
+    Greet::request(server.client(), "Jane".into()).await?;
+    RELEASE.notify_one();
+    assert!(waiting.await??);
+
+    let calls = server.recorded_calls();
+    assert_eq!(calls[0].request["method"], "wait_for_release");
+    assert_eq!(calls[0].response.as_ref().unwrap()["result"], true);
+    assert_eq!(calls[1].request["method"], "greet");
+
+    Ok(())
+}
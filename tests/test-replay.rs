@@ -0,0 +1,172 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::unused_unit)]
+
+use jsonrpsee::core::ClientError;
+use openspec_jsonrpsee::{ReplayClient, SpecModule, batch_request, rpc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[rpc]
+/// Stands for a backend method needing its context (e.g. a database pool).
+fn get_user(#[context] calls: &Arc<AtomicUsize>, id: u32) -> Result<User, String> {
+    calls.fetch_add(1, Ordering::Relaxed);
+    match id {
+        0 => Err("no user with id 0".into()),
+        id => Ok(User {
+            id,
+            name: format!("user-{id}"),
+        }),
+    }
+}
+
+#[rpc]
+fn increment(#[context] count: &Arc<AtomicUsize>) -> usize {
+    count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+fn counter_module(count: &Arc<AtomicUsize>) -> SpecModule<Arc<AtomicUsize>> {
+    let mut module = SpecModule::new(count.clone());
+    module
+        .add_method(Increment)
+        .expect("method should register");
+    module
+}
+
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("openspec-{name}-{}.json", std::process::id()))
+}
+
+#[tokio::test]
+async fn test_record_and_replay() -> Result<(), Box<dyn std::error::Error>> {
+    let path = fixture_path("replay");
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    // Record against the real module
+    let mut module = SpecModule::new(calls.clone());
+    module.add_method(GetUser)?;
+    let client = ReplayClient::record(module, &path);
+    assert!(client.is_recording());
+    let user = GetUser::request(&client, 7).await?;
+    let missing = GetUser::request(&client, 0).await?;
+    client.save()?;
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    // Replay offline, without the module or its context
+    let client = ReplayClient::replay(&path)?;
+    assert!(!client.is_recording());
+    assert_eq!(GetUser::request(&client, 7).await?, user);
+    assert_eq!(GetUser::request(&client, 0).await?, missing);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    // Calls that weren't recorded fail, params violating the spec just like on the server
+    let error = GetUser::request(&client, 8)
+        .await
+        .expect_err("wasn't recorded");
+    assert!(matches!(error, ClientError::Custom(_)));
+    let error = jsonrpsee::core::client::ClientT::request::<User, _>(
+        &client,
+        "get_user",
+        jsonrpsee::rpc_params!["seven"],
+    )
+    .await
+    .expect_err("params should be invalid");
+    let ClientError::Call(error) = error else {
+        panic!("expected a call error, got {error}")
+    };
+    assert_eq!(error.code(), -32602);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_repeated_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let path = fixture_path("replay-repeated");
+    let count = Arc::new(AtomicUsize::new(0));
+    let client = ReplayClient::record(counter_module(&count), &path);
+    assert_eq!(Increment::request(&client).await?, 1);
+    assert_eq!(Increment::request(&client).await?, 2);
+    client.save()?;
+
+    // Replayed in the order they were recorded, a same call having different results
+    let client = ReplayClient::replay(&path)?;
+    assert_eq!(Increment::request(&client).await?, 1);
+    assert_eq!(Increment::request(&client).await?, 2);
+    let error = Increment::request(&client)
+        .await
+        .expect_err("only two were recorded");
+    let ClientError::Custom(message) = error else {
+        panic!("expected a custom error, got {error}")
+    };
+    assert!(message.contains("were all replayed"), "{message}");
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_record_and_replay_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let path = fixture_path("replay-batch");
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut module = SpecModule::new(calls.clone());
+    module.add_method(GetUser)?;
+    let client = ReplayClient::record(module, &path);
+    let (user, missing) =
+        batch_request(&client, (GetUser::batch_item(7), GetUser::batch_item(0))).await?;
+    let (user, missing) = (user?, missing?);
+    client.save()?;
+
+    // Recorded as single calls, replayed batched or not
+    let client = ReplayClient::replay(&path)?;
+    let (replayed,) = batch_request(&client, (GetUser::batch_item(7),)).await?;
+    assert_eq!(replayed?, user);
+    assert_eq!(GetUser::request(&client, 0).await?, missing);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_record_or_replay() -> Result<(), Box<dyn std::error::Error>> {
+    let path = fixture_path("record-or-replay");
+    let count = Arc::new(AtomicUsize::new(0));
+
+    // Recorded while the fixture is missing
+    let client = ReplayClient::record_or_replay(&path, || counter_module(&count))?;
+    assert!(client.is_recording());
+    assert_eq!(Increment::request(&client).await?, 1);
+    client.save()?;
+
+    // Replayed once it exists, without creating the module
+    let client = ReplayClient::record_or_replay(&path, || -> SpecModule<()> {
+        panic!("the module shouldn't be created when replaying")
+    })?;
+    assert!(!client.is_recording());
+    assert_eq!(Increment::request(&client).await?, 1);
+
+    // Recorded again when updating, the only test of this file reading the variable
+    // SAFETY: no other test of this file reads or writes the environment
+    unsafe { std::env::set_var("OPENSPEC_UPDATE", "1") };
+    let client = ReplayClient::record_or_replay(&path, || counter_module(&count));
+    unsafe { std::env::remove_var("OPENSPEC_UPDATE") };
+    let client = client?;
+    assert!(client.is_recording());
+    assert_eq!(Increment::request(&client).await?, 2);
+    client.save()?;
+    let client = ReplayClient::replay(&path)?;
+    assert_eq!(Increment::request(&client).await?, 2);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
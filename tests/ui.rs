@@ -0,0 +1,7 @@
+/// Checks the diagnostics of `#[rpc]` on unsupported signatures, regenerate the expected output
+/// with `TRYBUILD=overwrite cargo test --test ui`
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
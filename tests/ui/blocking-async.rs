@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc(blocking)]
+async fn ping() -> String {
+    "pong".into()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::{CancellationToken, rpc};
+
+#[rpc]
+fn ping(#[cancellation] _token: CancellationToken) -> String {
+    "pong".into()
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn increment(#[context] count: &mut u64) -> u64 {
+    *count += 1;
+    *count
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use openspec_jsonrpsee::rpc;
+
+struct Database;
+
+#[rpc]
+fn ping(#[context] _db: Database) -> String {
+    "pong".into()
+}
+
+fn main() {}
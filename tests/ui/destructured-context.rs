@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn greet(#[context] (prefix, _): (String, u64), name: String) -> String {
+    format!("{prefix}{name}")
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn greet(#[context] prefix: &str, #[context] suffix: &str, name: String) -> String {
+    format!("{prefix}{name}{suffix}")
+}
+
+fn main() {}
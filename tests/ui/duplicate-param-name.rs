@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn add((a, _): (u64, u64), arg0: u64) -> u64 {
+    a + arg0
+}
+
+fn main() {}
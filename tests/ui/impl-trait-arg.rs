@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn describe(value: impl std::fmt::Display) -> String {
+    value.to_string()
+}
+
+fn main() {}
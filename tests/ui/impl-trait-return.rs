@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn describe(value: u64) -> impl std::fmt::Display {
+    value
+}
+
+fn main() {}
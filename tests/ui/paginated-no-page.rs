@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::{Page, rpc};
+
+#[rpc(paginated)]
+fn list_users(team: String) -> Page<String> {
+    Page::last(vec![team])
+}
+
+fn main() {}
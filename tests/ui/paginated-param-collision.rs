@@ -0,0 +1,21 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc(paginated)]
+fn list_users(cursor: String, page: openspec_jsonrpsee::PageRequest<u32>) -> openspec_jsonrpsee::Page<String, u32> {
+    let _ = page;
+    openspec_jsonrpsee::Page::last(vec![cursor])
+}
+
+#[rpc(paginated)]
+fn list_teams(#[param(name = "limit")] max: u32, page: openspec_jsonrpsee::PageRequest<u32>) -> openspec_jsonrpsee::Page<u32, u32> {
+    let _ = page;
+    openspec_jsonrpsee::Page::last(vec![max])
+}
+
+#[rpc(paginated)]
+fn list_groups(r#limit: u32, page: openspec_jsonrpsee::PageRequest<u32>) -> openspec_jsonrpsee::Page<u32, u32> {
+    let _ = page;
+    openspec_jsonrpsee::Page::last(vec![r#limit])
+}
+
+fn main() {}
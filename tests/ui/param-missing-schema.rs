@@ -0,0 +1,15 @@
+use openspec_jsonrpsee::rpc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[rpc]
+fn norm(point: Point) -> i64 {
+    point.x.abs() + point.y.abs()
+}
+
+fn main() {}
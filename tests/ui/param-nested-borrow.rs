@@ -0,0 +1,13 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+fn greet(name: Option<&str>) -> String {
+    format!("Hello {}", name.unwrap_or("you"))
+}
+
+#[rpc]
+fn join(words: &[&str]) -> String {
+    words.join(" ")
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use openspec_jsonrpsee::rpc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[rpc]
+fn origin() -> Point {
+    Point { x: 0, y: 0 }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use openspec_jsonrpsee::rpc;
+
+struct Service;
+
+impl Service {
+    #[rpc]
+    fn ping(&self) -> String {
+        "pong".into()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::{RpcStream, rpc};
+
+#[rpc(timeout = "5s")]
+async fn export_logs(count: usize) -> RpcStream<String> {
+    RpcStream::iter((0..count).map(|i| format!("log {i}")))
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc(timeout = "0s")]
+async fn ping() -> String {
+    "pong".into()
+}
+
+fn main() {}
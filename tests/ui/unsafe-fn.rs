@@ -0,0 +1,8 @@
+use openspec_jsonrpsee::rpc;
+
+#[rpc]
+unsafe fn read(address: usize) -> u8 {
+    unsafe { *(address as *const u8) }
+}
+
+fn main() {}